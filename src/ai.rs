@@ -1,19 +1,19 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use lazy_static::lazy_static;
 use reqwest::blocking::Client;
+use std::io::{BufRead, BufReader};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
 use std::time::Duration;
 
 use crate::config;
+use crate::conventional::{self, ConventionalCommit, ParseError};
 
 use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
 
-/// Valid conventional commit types
-const VALID_TYPES: [&str; 8] = [
-    "feat", "fix", "chore", "docs", "style", "refactor", "test", "perf",
-];
-
 lazy_static! {
     static ref GITMOJI_MAP: HashMap<&'static str, Vec<&'static str>> = {
         let mut map = HashMap::new();
@@ -58,90 +58,175 @@ lazy_static! {
             "⚡", "🚀", "💨", "🔥", "⚡️", "📈", "💫", "🏃"
         ]);
 
+        // Build system and dependencies (build)
+        map.insert("build", vec![
+            "📦️", "🏗️", "👷", "🔨", "⬆️", "⬇️", "📌", "➕"
+        ]);
+
+        // Continuous integration (ci)
+        map.insert("ci", vec![
+            "👷", "💚", "🤖", "🔁", "⚙️", "🚦", "🧰", "🛠️"
+        ]);
+
+        // Reverts (revert)
+        map.insert("revert", vec![
+            "⏪️", "↩️", "🔙", "🔄", "⏮️", "🧯", "🚧", "🗑️"
+        ]);
+
         map
     };
 }
 
-/// Cleans and validates AI-generated commit messages with optional gitmoji support
-pub fn clean_commit_message_from_ai(message: &str, use_gitmoji: bool) -> String {
-    let first_line = message.lines().next().unwrap_or("").trim();
-    let mut cleaned = first_line.to_string();
-
-    // Remove common prefixes and quotes
-    for prefix in [
-        "commit",
-        "Commit:",
-        "Commit message:",
-        "\"",
-        "'",
-        "```",
-        "`",
-    ] {
-        cleaned = cleaned.replace(prefix, "");
-    }
-    cleaned = cleaned.trim().to_string();
-
-    // Validate conventional commit format
-    if cleaned.is_empty() {
-        return if use_gitmoji {
-            "🔧 chore: update code".to_string()
-        } else {
-            "chore: update code".to_string()
-        };
+/// Splits a leading gitmoji from the rest of the message
+pub fn split_leading_emoji(message: &str) -> (Option<&str>, &str) {
+    if has_emoji(message) {
+        if let Some((emoji, rest)) = message.trim_start().split_once(' ') {
+            return (Some(emoji), rest.trim_start());
+        }
     }
+    (None, message)
+}
 
-    // Check if it follows type(scope): description format
-    if !cleaned.contains(':') {
-        return if use_gitmoji {
-            "🔧 chore: update code".to_string()
-        } else {
-            "chore: update code".to_string()
+/// Parses a (possibly gitmoji-prefixed) message and checks its type and scope
+/// against the ones allowed by the configuration
+pub fn validate_commit_message(
+    message: &str,
+    commit_config: &config::CommitConfig,
+) -> Result<ConventionalCommit, ParseError> {
+    let (_, message) = split_leading_emoji(message);
+    let commit = ConventionalCommit::parse(message)?;
+    commit.check_type(&commit_config.type_names())?;
+    commit.check_scope(&commit_config.scope_names())?;
+    Ok(commit)
+}
+
+/// Cleans and validates AI-generated commit messages with optional gitmoji support.
+/// When `commit.body` is enabled, the lines after the subject are kept as a wrapped body and footers.
+/// Fails when the output has no conventional header with an allowed type.
+pub fn clean_commit_message_from_ai(
+    message: &str,
+    use_gitmoji: bool,
+    commit_config: &config::CommitConfig,
+) -> Result<String, ParseError> {
+    // Skip code fences and blank lines the model may wrap the message in
+    let mut lines = message
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("```"))
+        .skip_while(|line| line.trim().is_empty());
+    let first_line = lines.next().unwrap_or("").trim();
+
+    // Remove labels and quotes the model sometimes wraps the subject in
+    let mut subject = first_line;
+    for label in ["Commit message:", "commit message:", "Commit:", "commit:"] {
+        if let Some(rest) = subject.strip_prefix(label) {
+            subject = rest.trim_start();
+        }
+    }
+    let subject = subject
+        .trim_matches(|c| matches!(c, '"' | '\'' | '`'))
+        .trim();
+
+    // The parser expects a bare header, keep any emoji the model already added
+    let (existing_emoji, header) = split_leading_emoji(subject);
+
+    let mut commit = ConventionalCommit::parse_header(header)?;
+    commit.commit_type = commit.commit_type.to_lowercase();
+    commit.check_type(&commit_config.type_names())?;
+    // A scope outside the allowed list is dropped rather than rejecting the whole message
+    if commit.check_scope(&commit_config.scope_names()).is_err() {
+        commit.scope = None;
+    }
+
+    let mut cleaned = commit.header();
+
+    // Add gitmoji if requested and not already present
+    if use_gitmoji {
+        cleaned = match existing_emoji {
+            Some(emoji) => format!("{emoji} {cleaned}"),
+            None => add_gitmoji_to_commit(&cleaned),
         };
     }
 
-    // Ensure it starts with a valid type
-    let parts: Vec<&str> = cleaned.split(':').collect();
-    if parts.len() < 2 {
-        return "".to_string();
+    if commit_config.body {
+        let body = format_body(&lines.collect::<Vec<_>>().join("\n"));
+        if !body.is_empty() {
+            cleaned = format!("{cleaned}\n\n{body}");
+        }
     }
 
-    let type_part = parts[0];
-    let has_valid_type = VALID_TYPES.iter().any(|&t| type_part.starts_with(t));
+    Ok(cleaned)
+}
 
-    if !has_valid_type {
-        return "".to_string();
-    }
+/// Maximum width of body lines, as recommended by git
+const BODY_WRAP_WIDTH: usize = 72;
 
-    // Add gitmoji if requested and not already present
-    if use_gitmoji && !has_emoji(&cleaned) {
-        cleaned = add_gitmoji_to_commit(&cleaned);
+/// Wraps text to the given width, breaking on whitespace; continuation lines start with `indent`
+pub fn wrap_text(text: &str, width: usize, indent: &str) -> String {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+
+    for word in text.split_whitespace() {
+        if current.is_empty() {
+            current.push_str(word);
+        } else if current.chars().count() + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut current));
+            current = format!("{indent}{word}");
+        } else {
+            current.push(' ');
+            current.push_str(word);
+        }
+    }
+    if !current.is_empty() {
+        lines.push(current);
     }
 
-    cleaned
+    lines.join("\n")
 }
 
-/// Check if a commit message already has an emoji
-fn has_emoji(message: &str) -> bool {
-    // Check if the message starts with an emoji (after any whitespace)
-    let trimmed = message.trim_start();
-    if trimmed.is_empty() {
-        return false;
+/// Re-wraps the body paragraphs generated by the model and keeps footers verbatim
+fn format_body(raw_body: &str) -> String {
+    let mut paragraphs = Vec::new();
+
+    for paragraph in raw_body.split("\n\n") {
+        let lines: Vec<&str> = paragraph
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect();
+        if lines.is_empty() {
+            continue;
+        }
+
+        if lines.iter().all(|line| conventional::is_footer_line(line)) {
+            paragraphs.push(lines.join("\n"));
+        } else if lines
+            .iter()
+            .all(|line| line.starts_with("- ") || line.starts_with("* "))
+        {
+            // Keep bullet lists, wrapping each item with a hanging indent
+            let items: Vec<String> = lines
+                .iter()
+                .map(|line| wrap_text(line, BODY_WRAP_WIDTH, "  "))
+                .collect();
+            paragraphs.push(items.join("\n"));
+        } else {
+            paragraphs.push(wrap_text(&lines.join(" "), BODY_WRAP_WIDTH, ""));
+        }
     }
 
-    // Simple emoji detection - look for Unicode emoji characters
-    // This covers most common emoji ranges
-    let first_char = trimmed.chars().next().unwrap();
-    let emoji_ranges = [
-        (0x1F600..=0x1F64F), // Emoticons
-        (0x1F300..=0x1F5FF), // Miscellaneous Symbols and Pictographs
-        (0x1F680..=0x1F6FF), // Transport and Map Symbols
-        (0x1F1E0..=0x1F1FF), // Regional Indicator Symbols
-        (0x2600..=0x26FF),   // Miscellaneous Symbols
-        (0x2700..=0x27BF),   // Dingbats
-    ];
+    paragraphs.join("\n\n")
+}
 
-    let char_code = first_char as u32;
-    emoji_ranges.iter().any(|range| range.contains(&char_code))
+/// Check if a commit message already has an emoji: a leading token of symbols only,
+/// followed by a space. Any emoji counts, including variation selectors and ZWJ sequences
+/// (`⬆️`, `🧑‍💻`), while words in non-Latin scripts do not.
+fn has_emoji(message: &str) -> bool {
+    message
+        .trim_start()
+        .split_once(' ')
+        .is_some_and(|(token, _)| {
+            !token.is_empty() && token.chars().all(|c| !c.is_ascii() && !c.is_alphanumeric())
+        })
 }
 
 /// Add appropriate gitmoji to a commit message based on its type
@@ -174,25 +259,531 @@ fn add_gitmoji_to_commit(message: &str) -> String {
     format!("🔧 {message}")
 }
 
-/// Generates a commit message using the configured AI model
+/// Per-request sampling overrides, used to get different completions for the same prompt
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sampling {
+    pub seed: Option<i64>,
+    pub temperature: Option<f32>,
+}
+
+/// A backend able to turn a prompt into a raw completion
+pub trait LlmProvider: Send {
+    /// Sends the prompt to the model and returns its raw text output
+    fn generate(&self, prompt: &str, sampling: &Sampling) -> Result<String>;
+
+    /// Streams the completion, calling `on_token` for each chunk as it arrives.
+    /// Returning an error from `on_token` aborts the request.
+    fn generate_stream(
+        &self,
+        prompt: &str,
+        on_token: &mut dyn FnMut(&str) -> Result<()>,
+    ) -> Result<String> {
+        let output = self.generate(prompt, &Sampling::default())?;
+        on_token(&output)?;
+        Ok(output)
+    }
+
+    /// Context window the model runs with, in tokens, when the server can tell
+    fn context_window(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// Context window Ollama gives a model when neither the request nor the Modelfile sets `num_ctx`
+const OLLAMA_DEFAULT_NUM_CTX: usize = 4096;
+
+/// Timeout for `/api/show`, which only reads model metadata
+const SHOW_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Ollama's native `/api/generate` endpoint
+pub struct OllamaProvider {
+    client: Client,
+    model: String,
+    host: String,
+    timeout: Duration,
+    keep_alive: Option<String>,
+    options: serde_json::Map<String, serde_json::Value>,
+}
+
+impl OllamaProvider {
+    /// `context_window` is sent as `num_ctx` when the `[ollama]` table does not set one, so
+    /// Ollama runs the prompt in the window the diff was sized for instead of cutting it
+    pub fn new(model: &str, settings: &config::OllamaConfig, context_window: Option<u32>) -> Self {
+        // Only send the options the user actually set so Ollama keeps its own defaults
+        let mut options = serde_json::Map::new();
+        if let Some(temperature) = settings.temperature {
+            options.insert("temperature".to_string(), temperature.into());
+        }
+        if let Some(top_p) = settings.top_p {
+            options.insert("top_p".to_string(), top_p.into());
+        }
+        if let Some(num_ctx) = settings.num_ctx.or(context_window) {
+            options.insert("num_ctx".to_string(), num_ctx.into());
+        }
+        if let Some(seed) = settings.seed {
+            options.insert("seed".to_string(), seed.into());
+        }
+
+        Self {
+            client: Client::new(),
+            model: model.to_string(),
+            host: settings.host.trim_end_matches('/').to_string(),
+            timeout: Duration::from_secs(settings.timeout_secs),
+            keep_alive: settings.keep_alive.clone(),
+            options,
+        }
+    }
+
+    fn send(
+        &self,
+        prompt: &str,
+        stream: bool,
+        sampling: &Sampling,
+    ) -> Result<reqwest::blocking::Response> {
+        let mut options = self.options.clone();
+        if let Some(seed) = sampling.seed {
+            options.insert("seed".to_string(), seed.into());
+        }
+        if let Some(temperature) = sampling.temperature {
+            options.insert("temperature".to_string(), temperature.into());
+        }
+
+        let mut body = serde_json::json!({
+            "model": self.model,
+            "prompt": prompt,
+            "stream": stream,
+            "options": options
+        });
+        if let Some(keep_alive) = &self.keep_alive {
+            body["keep_alive"] = keep_alive.clone().into();
+        }
+
+        let response = self
+            .client
+            .post(format!("{}/api/generate", self.host))
+            .json(&body)
+            .timeout(self.timeout)
+            .send()?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "Ollama returned {}: {}",
+                response.status(),
+                response.text().unwrap_or_default()
+            ));
+        }
+
+        Ok(response)
+    }
+}
+
+impl LlmProvider for OllamaProvider {
+    fn generate(&self, prompt: &str, sampling: &Sampling) -> Result<String> {
+        let json: serde_json::Value = self.send(prompt, false, sampling)?.json()?;
+        let raw_msg = json.get("response").and_then(|r| r.as_str()).unwrap_or("");
+
+        Ok(raw_msg.to_string())
+    }
+
+    fn generate_stream(
+        &self,
+        prompt: &str,
+        on_token: &mut dyn FnMut(&str) -> Result<()>,
+    ) -> Result<String> {
+        let response = self.send(prompt, true, &Sampling::default())?;
+        let mut output = String::new();
+
+        // Ollama streams one JSON object per line
+        for line in BufReader::new(response).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let chunk: serde_json::Value = serde_json::from_str(&line)?;
+            if let Some(error) = chunk.get("error").and_then(|e| e.as_str()) {
+                return Err(anyhow!("Ollama error: {error}"));
+            }
+            if let Some(token) = chunk.get("response").and_then(|r| r.as_str()) {
+                output.push_str(token);
+                on_token(token)?;
+            }
+            if chunk.get("done").and_then(|d| d.as_bool()).unwrap_or(false) {
+                break;
+            }
+        }
+
+        Ok(output)
+    }
+
+    fn context_window(&self) -> Option<usize> {
+        if let Some(num_ctx) = self.options.get("num_ctx").and_then(|n| n.as_u64()) {
+            return Some(num_ctx as usize);
+        }
+
+        let json: serde_json::Value = self
+            .client
+            .post(format!("{}/api/show", self.host))
+            .json(&serde_json::json!({ "model": self.model }))
+            .timeout(SHOW_TIMEOUT)
+            .send()
+            .ok()?
+            .error_for_status()
+            .ok()?
+            .json()
+            .ok()?;
+
+        // `num_ctx` set in the Modelfile, as "num_ctx 8192" among the parameters
+        let parameters = json
+            .get("parameters")
+            .and_then(|p| p.as_str())
+            .unwrap_or("");
+        if let Some(num_ctx) = parameters.lines().find_map(|line| {
+            line.trim()
+                .strip_prefix("num_ctx")
+                .and_then(|value| value.trim().parse().ok())
+        }) {
+            return Some(num_ctx);
+        }
+
+        // Otherwise Ollama uses its default, capped by what the model was trained for
+        let trained = json
+            .get("model_info")
+            .and_then(|info| info.as_object())?
+            .iter()
+            .find(|(key, _)| key.ends_with(".context_length"))
+            .and_then(|(_, length)| length.as_u64())?;
+        Some((trained as usize).min(OLLAMA_DEFAULT_NUM_CTX))
+    }
+}
+
+/// OpenAI-compatible `/chat/completions` endpoint (llama.cpp server, vLLM, LM Studio, ...)
+pub struct OpenAiProvider {
+    client: Client,
+    model: String,
+    base_url: String,
+    api_key: Option<String>,
+    timeout: Duration,
+}
+
+impl OpenAiProvider {
+    pub fn new(model: &str, settings: &config::OpenAiConfig) -> Self {
+        Self {
+            client: Client::new(),
+            model: model.to_string(),
+            base_url: settings.base_url.trim_end_matches('/').to_string(),
+            api_key: settings.api_key.clone(),
+            timeout: Duration::from_secs(settings.timeout_secs),
+        }
+    }
+
+    fn send(
+        &self,
+        prompt: &str,
+        stream: bool,
+        sampling: &Sampling,
+    ) -> Result<reqwest::blocking::Response> {
+        let mut body = serde_json::json!({
+            "model": self.model,
+            "messages": [{ "role": "user", "content": prompt }],
+            "stream": stream
+        });
+        if let Some(seed) = sampling.seed {
+            body["seed"] = seed.into();
+        }
+        if let Some(temperature) = sampling.temperature {
+            body["temperature"] = temperature.into();
+        }
+
+        let mut request = self
+            .client
+            .post(format!("{}/chat/completions", self.base_url))
+            .json(&body)
+            .timeout(self.timeout);
+
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+
+        let response = request.send()?;
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "OpenAI-compatible server returned {}: {}",
+                response.status(),
+                response.text().unwrap_or_default()
+            ));
+        }
+
+        Ok(response)
+    }
+}
+
+impl LlmProvider for OpenAiProvider {
+    fn generate(&self, prompt: &str, sampling: &Sampling) -> Result<String> {
+        let json: serde_json::Value = self.send(prompt, false, sampling)?.json()?;
+        let raw_msg = json
+            .pointer("/choices/0/message/content")
+            .and_then(|c| c.as_str())
+            .unwrap_or("");
+
+        Ok(raw_msg.to_string())
+    }
+
+    fn generate_stream(
+        &self,
+        prompt: &str,
+        on_token: &mut dyn FnMut(&str) -> Result<()>,
+    ) -> Result<String> {
+        let response = self.send(prompt, true, &Sampling::default())?;
+        let mut output = String::new();
+
+        // Server-sent events: `data: {json}` lines, terminated by `data: [DONE]`
+        for line in BufReader::new(response).lines() {
+            let line = line?;
+            let Some(data) = line.strip_prefix("data:").map(str::trim) else {
+                continue;
+            };
+            if data == "[DONE]" {
+                break;
+            }
+
+            let chunk: serde_json::Value = serde_json::from_str(data)?;
+            if let Some(token) = chunk
+                .pointer("/choices/0/delta/content")
+                .and_then(|c| c.as_str())
+            {
+                output.push_str(token);
+                on_token(token)?;
+            }
+        }
+
+        Ok(output)
+    }
+}
+
+/// Builds the provider selected in the configuration
+pub fn create_provider(configuration: &config::Config) -> Box<dyn LlmProvider> {
+    match configuration.provider {
+        config::Provider::Ollama => Box::new(OllamaProvider::new(
+            &configuration.model,
+            &configuration.ollama,
+            configuration.context_window,
+        )),
+        config::Provider::OpenAi => Box::new(OpenAiProvider::new(
+            &configuration.model,
+            &configuration.openai,
+        )),
+    }
+}
+
+/// Generates a commit message using the configured AI model.
+/// Output that is not a conventional commit fails with a `ParseError`.
 pub fn generate_commit_message(
     prompt: &str,
     configuration: &config::Config,
     use_gitmoji: bool,
 ) -> Result<String> {
-    let client = Client::new();
-    let response = client
-        .post("http://localhost:11434/api/generate")
-        .json(&serde_json::json!({
-            "model": configuration.model,
-            "prompt": prompt,
-            "stream": false
-        }))
-        .timeout(Duration::from_secs(45))
-        .send()?;
+    let provider = create_provider(configuration);
+    let raw_msg = provider.generate(prompt, &Sampling::default())?;
+
+    Ok(clean_commit_message_from_ai(
+        &raw_msg,
+        use_gitmoji,
+        &configuration.commit,
+    )?)
+}
+
+/// Temperature used for candidates when none is configured, high enough to get variety
+const CANDIDATE_TEMPERATURE: f32 = 0.8;
+
+/// Seed and temperature set for the active provider; the OpenAI-compatible one has none
+fn configured_sampling(configuration: &config::Config) -> Sampling {
+    match configuration.provider {
+        config::Provider::Ollama => Sampling {
+            seed: configuration.ollama.seed,
+            temperature: configuration.ollama.temperature,
+        },
+        config::Provider::OpenAi => Sampling::default(),
+    }
+}
+
+/// Generates up to `count` distinct commit messages by varying the sampling seed.
+/// Messages already in `existing` are skipped, so the result only holds new suggestions.
+pub fn generate_candidates(
+    prompt: &str,
+    configuration: &config::Config,
+    use_gitmoji: bool,
+    count: usize,
+    existing: &[String],
+    on_progress: &mut dyn FnMut(usize),
+) -> Result<Vec<String>> {
+    let provider = create_provider(configuration);
+
+    let configured = configured_sampling(configuration);
+
+    // Start from the configured seed when there is one so runs stay reproducible
+    let base_seed = configured.seed.unwrap_or_else(|| {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|elapsed| elapsed.subsec_nanos() as i64)
+            .unwrap_or_default()
+    });
+    let temperature = configured.temperature.unwrap_or(CANDIDATE_TEMPERATURE);
+
+    let normalize = |message: &str| message.trim().to_lowercase();
+    let mut seen: Vec<String> = existing.iter().map(|m| normalize(m)).collect();
+    let mut candidates = Vec::new();
+
+    for index in 0..count {
+        on_progress(index + 1);
+
+        let sampling = Sampling {
+            seed: Some(base_seed.wrapping_add(existing.len() as i64 + index as i64)),
+            temperature: Some(temperature),
+        };
+        let raw_msg = provider.generate(prompt, &sampling)?;
+        // Output that is not a conventional commit is simply not offered
+        let Ok(message) =
+            clean_commit_message_from_ai(&raw_msg, use_gitmoji, &configuration.commit)
+        else {
+            continue;
+        };
 
-    let json: serde_json::Value = response.json()?;
-    let raw_msg = json.get("response").and_then(|r| r.as_str()).unwrap_or("");
+        let key = normalize(&message);
+        if !seen.contains(&key) {
+            seen.push(key);
+            candidates.push(message);
+        }
+    }
 
-    Ok(clean_commit_message_from_ai(raw_msg, use_gitmoji))
+    Ok(candidates)
+}
+
+/// Progress of a streamed generation running on a background thread
+enum StreamEvent {
+    Token(String),
+    Done(Result<String>),
+}
+
+/// Generates a commit message while streaming the raw tokens to `on_token`.
+///
+/// The request runs on a background thread so `should_cancel` can be polled
+/// even while the model is still evaluating the prompt; when it returns true
+/// the request is abandoned and a "Generation cancelled by user" error is returned.
+/// Output that is not a conventional commit fails with a `ParseError`.
+pub fn stream_commit_message(
+    prompt: &str,
+    configuration: &config::Config,
+    use_gitmoji: bool,
+    on_token: &mut dyn FnMut(&str),
+    should_cancel: &dyn Fn() -> bool,
+) -> Result<String> {
+    let provider = create_provider(configuration);
+    let prompt = prompt.to_string();
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let (sender, receiver) = mpsc::channel();
+
+    let worker_cancelled = Arc::clone(&cancelled);
+    thread::spawn(move || {
+        let token_sender = sender.clone();
+        let result = provider.generate_stream(&prompt, &mut |token| {
+            if worker_cancelled.load(Ordering::SeqCst) {
+                return Err(anyhow!("Generation cancelled by user"));
+            }
+            token_sender
+                .send(StreamEvent::Token(token.to_string()))
+                .map_err(|_| anyhow!("Generation cancelled by user"))
+        });
+        let _ = sender.send(StreamEvent::Done(result));
+    });
+
+    loop {
+        if should_cancel() {
+            // Dropping the receiver makes the worker stop at its next token
+            cancelled.store(true, Ordering::SeqCst);
+            return Err(anyhow!("Generation cancelled by user"));
+        }
+
+        match receiver.recv_timeout(Duration::from_millis(100)) {
+            Ok(StreamEvent::Token(token)) => on_token(&token),
+            Ok(StreamEvent::Done(result)) => {
+                return Ok(clean_commit_message_from_ai(
+                    &result?,
+                    use_gitmoji,
+                    &configuration.commit,
+                )?);
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => continue,
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                return Err(anyhow!("Generation stopped unexpectedly"));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_any_leading_emoji() {
+        for (message, emoji) in [
+            ("🧪 test: x", "🧪"),
+            ("⬆️ build: x", "⬆️"),
+            ("⏪️ revert: x", "⏪️"),
+            ("🧑‍💻 feat: x", "🧑‍💻"),
+        ] {
+            assert_eq!(
+                split_leading_emoji(message),
+                (Some(emoji), &message[emoji.len() + 1..])
+            );
+        }
+        assert_eq!(split_leading_emoji("feat: x"), (None, "feat: x"));
+        assert_eq!(
+            split_leading_emoji("Исправлена ошибка"),
+            (None, "Исправлена ошибка")
+        );
+        // An emoji alone is not a prefix
+        assert_eq!(split_leading_emoji("🧪"), (None, "🧪"));
+    }
+
+    #[test]
+    fn every_gitmoji_validates() {
+        let commit_config = config::CommitConfig::default();
+        for (commit_type, emojis) in GITMOJI_MAP.iter() {
+            for emoji in emojis {
+                let message = format!("{emoji} {commit_type}(core): do something");
+                let commit = validate_commit_message(&message, &commit_config)
+                    .unwrap_or_else(|e| panic!("{message}: {e}"));
+                assert_eq!(commit.commit_type, *commit_type);
+            }
+        }
+    }
+
+    #[test]
+    fn build_ci_and_revert_gitmoji_survive_cleaning() {
+        let commit_config = config::CommitConfig::default();
+        for commit_type in ["build", "ci", "revert"] {
+            // Picked by Commayte
+            let cleaned = clean_commit_message_from_ai(
+                &format!("{commit_type}: update the pipeline"),
+                true,
+                &commit_config,
+            )
+            .unwrap();
+            assert!(
+                validate_commit_message(&cleaned, &commit_config).is_ok(),
+                "{cleaned}"
+            );
+
+            // Already written by the model
+            for emoji in &GITMOJI_MAP[commit_type] {
+                let output = format!("{emoji} {commit_type}(deps): update the pipeline");
+                let cleaned = clean_commit_message_from_ai(&output, true, &commit_config).unwrap();
+                assert_eq!(cleaned, output);
+                let commit = validate_commit_message(&cleaned, &commit_config).unwrap();
+                assert_eq!(commit.commit_type, commit_type);
+            }
+        }
+    }
 }
@@ -0,0 +1,282 @@
+use crate::ai;
+use crate::config;
+use crate::git::{self, FileDiff};
+
+/// Rough size of a token in code and English text
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Context window assumed when neither the configuration nor the provider tells
+const DEFAULT_CONTEXT_WINDOW: usize = 4096;
+
+/// Room kept for the model's answer
+const OUTPUT_RESERVE_TOKENS: usize = 512;
+
+/// Smallest diff budget, even when the context window is tiny
+const MIN_DIFF_TOKENS: usize = 256;
+
+/// Smallest part of a file worth showing, below that only its name is listed
+const MIN_FILE_TOKENS: usize = 48;
+
+/// Estimates how many tokens `text` takes in the prompt
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(CHARS_PER_TOKEN)
+}
+
+/// Characters that fit in `tokens`
+pub fn tokens_to_chars(tokens: usize) -> usize {
+    tokens * CHARS_PER_TOKEN
+}
+
+/// Context window of the configured model: `context_window` from the configuration,
+/// otherwise what the provider reports, otherwise a conservative default
+pub fn context_window(configuration: &config::Config) -> usize {
+    configuration
+        .context_window
+        .map(|window| window as usize)
+        .or_else(|| ai::create_provider(configuration).context_window())
+        .unwrap_or(DEFAULT_CONTEXT_WINDOW)
+}
+
+/// Tokens the diff may use in a `window`-token context, once the rest of the prompt
+/// (`prompt`, built with an empty diff) and the answer have their room
+pub fn diff_budget(window: usize, prompt: &str) -> usize {
+    window
+        .saturating_sub(estimate_tokens(prompt) + OUTPUT_RESERVE_TOKENS)
+        .max(MIN_DIFF_TOKENS)
+}
+
+/// How much a file's patch tells about the change, relative to the others
+fn importance(file: &FileDiff) -> usize {
+    let path = file.path.to_lowercase();
+    let name = path.rsplit('/').next().unwrap_or(&path);
+    let extension = name.rsplit_once('.').map_or("", |(_, extension)| extension);
+
+    if file.patch.contains("\ndeleted file mode") {
+        // The header already says it all
+        1
+    } else if path.starts_with("docs/")
+        || ["md", "rst", "txt", "adoc", "snap", "svg"].contains(&extension)
+        || name.contains(".min.")
+    {
+        1
+    } else if path.contains("test")
+        || path.contains("spec")
+        || ["json", "yaml", "yml", "toml", "xml", "ini", "cfg", "csv"].contains(&extension)
+    {
+        2
+    } else {
+        4
+    }
+}
+
+/// Most the list of left-out files can take: its heading, the longest lines it can show
+/// and the count of the others. Each line is counted with its line break.
+fn listing_tokens(files: &[FileDiff]) -> usize {
+    let mut lines: Vec<usize> = files
+        .iter()
+        .map(|file| estimate_tokens(&git::left_out_line(file)) + 1)
+        .collect();
+    lines.sort_unstable_by(|a, b| b.cmp(a));
+    let more = format!("- ... and {} more files", files.len());
+
+    estimate_tokens(git::LEFT_OUT_HEADER)
+        + 1
+        + lines.iter().take(git::MAX_LISTED_FILES).sum::<usize>()
+        + estimate_tokens(&more)
+        + 1
+}
+
+/// Splits `budget` tokens between files. When everything does not fit, the most important
+/// files are kept first, then each gets a share proportional to its importance; files
+/// smaller than their share leave the rest to the others. Returns the tokens given to each
+/// file, in the same order: 0 means it is only named, less than its size means it is cut.
+///
+/// The blank line between files, the truncation markers and the list of left-out files
+/// are counted too, so the diff `git::filter_diff` builds stays within `budget`.
+pub fn allocate(files: &[FileDiff], budget: usize) -> Vec<usize> {
+    let costs: Vec<usize> = files
+        .iter()
+        .map(|file| estimate_tokens(&file.patch))
+        .collect();
+    if costs.iter().sum::<usize>() + files.len() <= budget {
+        return costs;
+    }
+    let weights: Vec<usize> = files.iter().map(importance).collect();
+    // Separator and truncation marker of a file that is shown
+    let overhead = estimate_tokens(git::TRUNCATED_FILE_MARKER) + 2;
+
+    // Most important first, smaller before larger among equals
+    let mut order: Vec<usize> = (0..files.len()).collect();
+    order.sort_by(|&a, &b| weights[b].cmp(&weights[a]).then(costs[a].cmp(&costs[b])));
+    let mut kept = Vec::new();
+    let mut reserved = listing_tokens(files);
+    for index in order {
+        let minimum = costs[index].min(MIN_FILE_TOKENS) + overhead;
+        if reserved + minimum <= budget {
+            reserved += minimum;
+            kept.push(index);
+        }
+    }
+
+    // Files needing the least per unit of importance are served first, whatever they
+    // do not use is shared between the larger ones
+    kept.sort_by(|&a, &b| (costs[a] * weights[b]).cmp(&(costs[b] * weights[a])));
+    let mut allotted = vec![0; files.len()];
+    let mut remaining = budget.saturating_sub(listing_tokens(files) + kept.len() * overhead);
+    let mut weight_left: usize = kept.iter().map(|&index| weights[index]).sum();
+    for index in kept {
+        let share = remaining * weights[index] / weight_left;
+        let tokens = costs[index].min(share.max(MIN_FILE_TOKENS)).min(remaining);
+        // Too little left to show anything useful, the file is only named
+        if tokens >= costs[index].min(MIN_FILE_TOKENS) {
+            allotted[index] = tokens;
+            remaining -= tokens;
+        }
+        weight_left -= weights[index];
+    }
+    allotted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A new file of `lines` lines of about 40 characters (10 tokens) each
+    fn file(path: &str, lines: usize) -> FileDiff {
+        let mut patch = format!(
+            "diff --git a/{path} b/{path}\nnew file mode 100644\n--- /dev/null\n+++ b/{path}\n@@ -0,0 +1,{lines} @@\n"
+        );
+        for line in 0..lines {
+            patch.push_str(&format!("+let value_{line:04} = compute_something();\n"));
+        }
+        FileDiff {
+            path: path.to_string(),
+            patch,
+        }
+    }
+
+    fn diff_of(files: &[FileDiff]) -> String {
+        files.iter().map(|file| file.patch.as_str()).collect()
+    }
+
+    #[test]
+    fn estimates_tokens_from_characters() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abcd"), 1);
+        assert_eq!(estimate_tokens("abcde"), 2);
+        // Characters, not bytes
+        assert_eq!(estimate_tokens("éééé"), 1);
+        assert_eq!(tokens_to_chars(3), 12);
+    }
+
+    #[test]
+    fn ranks_source_above_tests_above_docs() {
+        assert_eq!(importance(&file("src/main.rs", 1)), 4);
+        assert_eq!(importance(&file("tests/cli.rs", 1)), 2);
+        assert_eq!(importance(&file("Cargo.toml", 1)), 2);
+        assert_eq!(importance(&file("README.md", 1)), 1);
+        assert_eq!(importance(&file("docs/guide.html", 1)), 1);
+        assert_eq!(importance(&file("assets/app.min.js", 1)), 1);
+
+        let mut deleted = file("src/old.rs", 1);
+        deleted.patch = deleted
+            .patch
+            .replace("\nnew file mode", "\ndeleted file mode");
+        assert_eq!(importance(&deleted), 1);
+    }
+
+    #[test]
+    fn gives_everything_when_it_fits() {
+        let files = [file("src/a.rs", 10), file("src/b.rs", 20)];
+        let costs: Vec<usize> = files
+            .iter()
+            .map(|file| estimate_tokens(&file.patch))
+            .collect();
+
+        assert_eq!(allocate(&files, 10_000), costs);
+    }
+
+    #[test]
+    fn cuts_large_files_to_share_the_budget() {
+        let files = [file("src/a.rs", 100), file("src/b.rs", 100)];
+        let allotted = allocate(&files, 1_000);
+
+        for (file, tokens) in files.iter().zip(&allotted) {
+            assert!(*tokens >= MIN_FILE_TOKENS);
+            assert!(*tokens < estimate_tokens(&file.patch));
+        }
+        assert_eq!(allotted[0], allotted[1]);
+    }
+
+    #[test]
+    fn favors_source_over_docs() {
+        let files = [file("README.md", 100), file("src/lib.rs", 100)];
+        let allotted = allocate(&files, 1_000);
+
+        assert!(allotted[1] > allotted[0]);
+    }
+
+    #[test]
+    fn small_files_leave_their_share_to_large_ones() {
+        let files = [file("src/small.rs", 2), file("src/large.rs", 200)];
+        let allotted = allocate(&files, 1_000);
+
+        assert_eq!(allotted[0], estimate_tokens(&files[0].patch));
+        assert!(allotted[1] > 1_000 / 2);
+    }
+
+    #[test]
+    fn only_names_files_that_do_not_fit() {
+        let files: Vec<FileDiff> = (0..20)
+            .map(|index| file(&format!("src/file_{index}.rs"), 50))
+            .collect();
+        let allotted = allocate(&files, 600);
+
+        assert!(allotted.contains(&0));
+        assert!(allotted.iter().any(|&tokens| tokens > 0));
+
+        let diff = git::filter_diff(
+            &diff_of(&files),
+            600,
+            &git::IgnoreRules::load(&Default::default()),
+        );
+        assert!(diff.contains(git::LEFT_OUT_HEADER));
+        assert!(diff.contains("- src/file_19.rs (+50 -0)"));
+    }
+
+    #[test]
+    fn fitted_diff_never_exceeds_the_budget() {
+        let rules = git::IgnoreRules::load(&Default::default());
+        let file_sets: Vec<Vec<FileDiff>> = vec![
+            vec![file("src/a.rs", 300)],
+            vec![
+                file("src/a.rs", 5),
+                file("README.md", 80),
+                file("src/b.rs", 150),
+            ],
+            (0..12)
+                .map(|index| file(&format!("tests/case_{index}.rs"), 10 + index * 7))
+                .collect(),
+            (0..60)
+                .map(|index| file(&format!("src/module_{index}/mod.rs"), 30))
+                .collect(),
+        ];
+
+        for files in &file_sets {
+            for budget in [300, 450, 700, 1_000, 1_500, 2_500, 4_000] {
+                let allotted = allocate(files, budget);
+                let diff = git::filter_diff(&diff_of(files), budget, &rules);
+
+                // Below the size of the list of names alone nothing can be shown
+                if allotted.iter().any(|&tokens| tokens > 0) {
+                    assert!(
+                        estimate_tokens(&diff) <= budget,
+                        "{} tokens for a budget of {budget} with {} files",
+                        estimate_tokens(&diff),
+                        files.len()
+                    );
+                }
+            }
+        }
+    }
+}
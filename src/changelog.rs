@@ -0,0 +1,442 @@
+use anyhow::{anyhow, Result};
+use colored::*;
+use std::fs;
+use std::path::Path;
+
+use crate::ai;
+use crate::config;
+use crate::conventional::ConventionalCommit;
+use crate::git;
+
+/// Heading of the section for changes not released yet
+const UNRELEASED_HEADING: &str = "## [Unreleased]";
+
+/// Header written when `--prepend` creates a new changelog
+const CHANGELOG_HEADER: &str = "# Changelog\n\n\
+    All notable changes to this project will be documented in this file.\n\n\
+    The format is based on [Keep a Changelog](https://keepachangelog.com/en/1.1.0/).\n\n";
+
+/// Keep a Changelog section for each commit type, in display order.
+/// Types not listed here (docs, chore, ci...) only appear with `--all`, under "Other".
+const SECTIONS: [(&str, &[&str]); 3] = [
+    ("Added", &["feat"]),
+    ("Changed", &["perf", "refactor", "revert"]),
+    ("Fixed", &["fix"]),
+];
+
+/// One changelog line, taken from a commit
+struct Entry {
+    commit_type: String,
+    scope: Option<String>,
+    description: String,
+    short_id: String,
+}
+
+impl Entry {
+    fn render(&self) -> String {
+        match &self.scope {
+            Some(scope) => format!("- **{scope}:** {} ({})\n", self.description, self.short_id),
+            None => format!("- {} ({})\n", self.description, self.short_id),
+        }
+    }
+}
+
+/// Parses a commit the way `commayte` writes them, ignoring a leading gitmoji.
+/// Messages with a malformed body still count when their header is valid.
+fn parse_commit(message: &str) -> Option<ConventionalCommit> {
+    let (_, message) = ai::split_leading_emoji(message);
+    ConventionalCommit::parse(message)
+        .or_else(|_| ConventionalCommit::parse_header(message.lines().next().unwrap_or("")))
+        .ok()
+}
+
+/// Converts a Unix timestamp to a `YYYY-MM-DD` date (UTC)
+fn format_date(timestamp: i64) -> String {
+    // Days-to-civil conversion from Howard Hinnant's date algorithms
+    let days = timestamp.div_euclid(86_400) + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// Renders entries sorted by scope (unscoped first), keeping commit order within a scope
+fn render_section(title: &str, entries: &mut [&Entry]) -> String {
+    entries.sort_by(|a, b| a.scope.cmp(&b.scope));
+    let lines: String = entries.iter().map(|entry| entry.render()).collect();
+    format!("### {title}\n\n{lines}\n")
+}
+
+/// Renders one Keep a Changelog release section from commits, oldest first
+fn render(
+    release: &str,
+    date: Option<&str>,
+    commits: &[git::CommitInfo],
+    include_all: bool,
+) -> (String, usize) {
+    let mut entries = Vec::new();
+    let mut breaking = Vec::new();
+    let mut skipped = 0;
+
+    for commit in commits.iter().filter(|commit| !commit.is_merge) {
+        let Some(parsed) = parse_commit(&commit.message) else {
+            skipped += 1;
+            continue;
+        };
+        let short_id = commit.id[..commit.id.len().min(7)].to_string();
+
+        if parsed.breaking {
+            // The footer explains what breaks better than the subject does
+            let description = parsed
+                .footers
+                .iter()
+                .find(|footer| footer.is_breaking())
+                .map(|footer| footer.value.clone())
+                .unwrap_or_else(|| parsed.description.clone());
+            breaking.push(Entry {
+                commit_type: parsed.commit_type.clone(),
+                scope: parsed.scope.clone(),
+                description,
+                short_id: short_id.clone(),
+            });
+        }
+        entries.push(Entry {
+            commit_type: parsed.commit_type.to_lowercase(),
+            scope: parsed.scope,
+            description: parsed.description,
+            short_id,
+        });
+    }
+
+    let heading = match date {
+        Some(date) => format!("## [{release}] - {date}\n\n"),
+        None => format!("## [{release}]\n\n"),
+    };
+    let mut markdown = heading;
+
+    if !breaking.is_empty() {
+        markdown.push_str(&render_section(
+            "⚠ Breaking Changes",
+            &mut breaking.iter().collect::<Vec<_>>(),
+        ));
+    }
+    for (title, types) in SECTIONS {
+        let mut section: Vec<&Entry> = entries
+            .iter()
+            .filter(|entry| types.contains(&entry.commit_type.as_str()))
+            .collect();
+        if !section.is_empty() {
+            markdown.push_str(&render_section(title, &mut section));
+        }
+    }
+    if include_all {
+        let mut other: Vec<&Entry> = entries
+            .iter()
+            .filter(|entry| {
+                !SECTIONS
+                    .iter()
+                    .any(|(_, types)| types.contains(&entry.commit_type.as_str()))
+            })
+            .collect();
+        if !other.is_empty() {
+            markdown.push_str(&render_section("Other", &mut other));
+        }
+    }
+
+    (markdown, skipped)
+}
+
+/// Inserts `section` above the newest release of an existing changelog.
+/// A section with the same heading (usually `[Unreleased]`) is replaced instead,
+/// so running the command again updates it. A versioned release also replaces the
+/// `[Unreleased]` section, whose changes it now documents.
+fn prepend(path: &Path, section: &str) -> Result<()> {
+    let existing = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            fs::write(path, format!("{CHANGELOG_HEADER}{section}"))?;
+            return Ok(());
+        }
+        Err(e) => return Err(anyhow!("Could not read {}: {e}", path.display())),
+    };
+
+    let heading = section.lines().next().unwrap_or("");
+    let release_name = heading.split(" - ").next().unwrap_or(heading);
+    let is_replaced = |name: &str| {
+        name == release_name || (release_name != UNRELEASED_HEADING && name == UNRELEASED_HEADING)
+    };
+
+    let release_starts: Vec<usize> = existing
+        .match_indices("## ")
+        .map(|(index, _)| index)
+        .filter(|index| *index == 0 || existing.as_bytes()[index - 1] == b'\n')
+        .collect();
+
+    let updated = match release_starts.first() {
+        Some(&start) => {
+            // Drop the sections at the top that the new one replaces
+            let end = release_starts
+                .iter()
+                .copied()
+                .find(|&index| {
+                    let name = existing[index..].lines().next().unwrap_or("");
+                    !is_replaced(name.split(" - ").next().unwrap_or(name))
+                })
+                .unwrap_or(existing.len());
+            format!("{}{section}{}", &existing[..start], &existing[end..])
+        }
+        None => format!("{}\n\n{section}", existing.trim_end()),
+    };
+
+    fs::write(path, updated)?;
+    Ok(())
+}
+
+pub fn run(
+    configuration: &config::Config,
+    from: Option<&str>,
+    to: &str,
+    release: Option<&str>,
+    include_all: bool,
+    prepend_to: Option<&Path>,
+) -> Result<()> {
+    let backend = git::open_backend(&configuration.git)?;
+
+    let from = match from {
+        Some(from) => Some(from.to_string()),
+        None => backend.previous_tag(to)?,
+    };
+    let range = match &from {
+        Some(from) => format!("{from}..{to}"),
+        None => to.to_string(),
+    };
+    let commits = backend.commits_in_range(&range)?;
+    if commits.is_empty() {
+        eprintln!("{}", format!("⚠️  No commits in {range}.").yellow());
+        return Ok(());
+    }
+
+    // `--to v1.2.0` documents that release, otherwise the changes are still unreleased
+    let release = match release {
+        Some(release) => Some(release.to_string()),
+        None if to != "HEAD" => Some(to.to_string()),
+        None => None,
+    };
+    let date = match (&release, commits.last()) {
+        (Some(_), Some(newest)) => Some(format_date(newest.time)),
+        _ => None,
+    };
+    let (section, skipped) = render(
+        release.as_deref().unwrap_or("Unreleased"),
+        date.as_deref(),
+        &commits,
+        include_all,
+    );
+
+    if skipped > 0 {
+        eprintln!(
+            "{}",
+            format!("⚠️  Skipped {skipped} commit(s) that are not conventional commits").yellow()
+        );
+    }
+
+    match prepend_to {
+        Some(path) => {
+            prepend(path, &section)?;
+            println!(
+                "✅ Added {} to {}",
+                section
+                    .lines()
+                    .next()
+                    .unwrap_or("")
+                    .trim_start_matches("## "),
+                path.display().to_string().bold()
+            );
+        }
+        None => print!("{section}"),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(id: &str, message: &str) -> git::CommitInfo {
+        git::CommitInfo {
+            id: id.repeat(40 / id.len()),
+            message: message.to_string(),
+            is_merge: false,
+            time: 0,
+        }
+    }
+
+    /// A changelog path of its own for each test, removed first
+    fn temp_changelog(name: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "commayte-changelog-{}-{name}.md",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn formats_dates() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(-1), "1969-12-31");
+        assert_eq!(format_date(1_700_000_000), "2023-11-14");
+        assert_eq!(format_date(1_735_689_599), "2024-12-31");
+    }
+
+    #[test]
+    fn formats_leap_days() {
+        // 2024 is a leap year
+        assert_eq!(format_date(1_709_164_800), "2024-02-29");
+        assert_eq!(format_date(1_709_251_200), "2024-03-01");
+        // 2000 is one too, being divisible by 400
+        assert_eq!(format_date(951_782_400), "2000-02-29");
+        assert_eq!(format_date(951_868_800), "2000-03-01");
+        // 1900 and 2100 are not, being divisible by 100 only
+        assert_eq!(format_date(-2_203_977_600), "1900-02-28");
+        assert_eq!(format_date(-2_203_891_200), "1900-03-01");
+        assert_eq!(format_date(4_107_456_000), "2100-02-28");
+        assert_eq!(format_date(4_107_542_400), "2100-03-01");
+    }
+
+    #[test]
+    fn groups_entries_by_section_and_scope() {
+        let commits = [
+            commit("a", "fix(ui): stop flickering"),
+            commit("b", "feat: add reword"),
+            commit("c", "feat(git): add a libgit2 backend"),
+            commit("d", "✨ feat: add changelog"),
+            commit("e", "perf: cache the prompt"),
+            commit("f", "docs: explain layers"),
+            commit("g", "not conventional"),
+            commit("h", "feat(ai): stream tokens"),
+        ];
+        let (markdown, skipped) = render("1.2.0", Some("2024-05-01"), &commits, false);
+
+        assert_eq!(skipped, 1);
+        assert_eq!(
+            markdown,
+            "## [1.2.0] - 2024-05-01\n\n\
+            ### Added\n\n\
+            - add reword (bbbbbbb)\n\
+            - add changelog (ddddddd)\n\
+            - **ai:** stream tokens (hhhhhhh)\n\
+            - **git:** add a libgit2 backend (ccccccc)\n\n\
+            ### Changed\n\n\
+            - cache the prompt (eeeeeee)\n\n\
+            ### Fixed\n\n\
+            - **ui:** stop flickering (aaaaaaa)\n\n"
+        );
+    }
+
+    #[test]
+    fn lists_breaking_changes_and_other_types() {
+        let commits = [
+            commit(
+                "a",
+                "feat(config)!: rename keys\n\nBREAKING CHANGE: ollama_host is now ollama.host",
+            ),
+            commit("b", "chore: bump deps"),
+        ];
+        let (markdown, _) = render("Unreleased", None, &commits, true);
+
+        assert_eq!(
+            markdown,
+            "## [Unreleased]\n\n\
+            ### ⚠ Breaking Changes\n\n\
+            - **config:** ollama_host is now ollama.host (aaaaaaa)\n\n\
+            ### Added\n\n\
+            - **config:** rename keys (aaaaaaa)\n\n\
+            ### Other\n\n\
+            - bump deps (bbbbbbb)\n\n"
+        );
+    }
+
+    #[test]
+    fn prepend_creates_the_file_with_a_header() {
+        let path = temp_changelog("create");
+        prepend(
+            &path,
+            "## [1.0.0] - 2024-01-01\n\n### Added\n\n- a (1234567)\n\n",
+        )
+        .unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with(CHANGELOG_HEADER));
+        assert!(content.ends_with("## [1.0.0] - 2024-01-01\n\n### Added\n\n- a (1234567)\n\n"));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn repeated_prepends_replace_the_same_release() {
+        let path = temp_changelog("repeat");
+        fs::write(
+            &path,
+            "# Changelog\n\n## [1.0.0] - 2024-01-01\n\n### Added\n\n- first (1111111)\n",
+        )
+        .unwrap();
+
+        prepend(&path, "## [Unreleased]\n\n### Fixed\n\n- one (2222222)\n\n").unwrap();
+        prepend(&path, "## [Unreleased]\n\n### Fixed\n\n- two (3333333)\n\n").unwrap();
+        prepend(
+            &path,
+            "## [1.1.0] - 2024-02-01\n\n### Fixed\n\n- two (3333333)\n\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "# Changelog\n\n\
+            ## [1.1.0] - 2024-02-01\n\n### Fixed\n\n- two (3333333)\n\n\
+            ## [1.0.0] - 2024-01-01\n\n### Added\n\n- first (1111111)\n"
+        );
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn a_release_replaces_the_unreleased_section() {
+        let path = temp_changelog("release");
+        fs::write(
+            &path,
+            "# Changelog\n\n\
+            ## [Unreleased]\n\n### Added\n\n- two (2222222)\n\n\
+            ## [1.0.0] - 2024-01-01\n\n### Added\n\n- first (1111111)\n",
+        )
+        .unwrap();
+
+        prepend(
+            &path,
+            "## [1.1.0] - 2024-02-01\n\n### Added\n\n- two (2222222)\n\n",
+        )
+        .unwrap();
+        prepend(
+            &path,
+            "## [Unreleased]\n\n### Fixed\n\n- three (3333333)\n\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "# Changelog\n\n\
+            ## [Unreleased]\n\n### Fixed\n\n- three (3333333)\n\n\
+            ## [1.1.0] - 2024-02-01\n\n### Added\n\n- two (2222222)\n\n\
+            ## [1.0.0] - 2024-01-01\n\n### Added\n\n- first (1111111)\n"
+        );
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,76 +1,625 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use colored::*;
+use serde::Serialize;
 
 use crate::ai;
+use crate::budget;
 use crate::config;
+use crate::conventional::ParseError;
 use crate::git;
 use crate::project;
 use crate::prompts;
-use crate::system;
+use crate::style;
 use crate::terminal;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
-pub fn run(use_emoji: bool, push: bool) -> Result<()> {
+/// Prints a commit message with its subject highlighted and the body below it
+pub fn print_commit_message(label: &str, message: &str) {
+    let (subject, body) = match message.split_once('\n') {
+        Some((subject, rest)) => (subject, rest.trim()),
+        None => (message, ""),
+    };
+
+    println!("📝 {} {}", label.bold().green(), subject.bold().white());
+    if !body.is_empty() {
+        println!();
+        for line in body.lines() {
+            if line.is_empty() {
+                println!();
+            } else {
+                println!("   {}", line.white());
+            }
+        }
+    }
+}
+
+/// How a run interacts with the user
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Menus to accept, edit or regenerate the message
+    Interactive,
+    /// Commit the first valid message without asking (`--yes`)
+    Commit,
+    /// Print the message without committing (`--dry-run`, or when there is no TTY)
+    Print,
+}
+
+/// Explains a failed git operation: the likely cause, git's own output and a hint
+fn print_git_failure(title: &str, error: &anyhow::Error, kind: git::FailureKind) {
+    println!(
+        "{} {}",
+        format!("❌ {title}:").bold().red(),
+        kind.description()
+    );
+    for line in error.to_string().lines() {
+        println!("   {}", line.dimmed());
+    }
+    if let Some(hint) = kind.hint() {
+        println!("💡 {hint}");
+    }
+}
+
+/// Saves a message that was not committed and tells the user how to get it back
+fn keep_message(message: &str) {
+    match git::save_message(message) {
+        Ok(()) => println!("💾 Message saved, run 'commayte --reuse' to use it again"),
+        Err(e) => println!("{} {}", "⚠️  Could not save the message:".yellow(), e),
+    }
+}
+
+/// Stages every modified or deleted tracked file, like `git commit -a`
+fn stage_tracked_changes(backend: &dyn git::GitBackend) -> Result<()> {
+    let tracked: Vec<String> = backend
+        .unstaged_changes()?
+        .into_iter()
+        .filter(|change| change.kind != git::ChangeKind::Untracked)
+        .map(|change| change.path)
+        .collect();
+
+    if !tracked.is_empty() {
+        backend.stage(&tracked)?;
+    }
+    Ok(())
+}
+
+/// Lets the user pick unstaged files to stage when nothing is staged yet.
+/// Returns false when there is nothing to stage or nothing was picked.
+fn stage_interactively(
+    backend: &dyn git::GitBackend,
+    configuration: &config::Config,
+) -> Result<bool> {
+    let changes = backend.unstaged_changes()?;
+    if changes.is_empty() {
+        return Ok(false);
+    }
+
+    println!("{}", "Nothing is staged yet.".yellow());
+    let items: Vec<String> = changes
+        .iter()
+        .map(|change| format!("{:<10} {}", change.kind.label(), change.path))
+        .collect();
+    // Lock files, build output and the like start unchecked
+    let rules = git::IgnoreRules::load(&configuration.ignore);
+    let defaults: Vec<bool> = changes
+        .iter()
+        .map(|change| !rules.is_ignored(&change.path))
+        .collect();
+
+    let selection = terminal::show_multi_select(
+        &items,
+        &defaults,
+        "Select files to stage (space to toggle, enter to confirm)",
+    )?;
+    if selection.is_empty() {
+        return Ok(false);
+    }
+
+    let paths: Vec<String> = selection
+        .into_iter()
+        .map(|index| changes[index].path.clone())
+        .collect();
+    backend.stage(&paths)?;
+    println!("✅ Staged {} file(s)", paths.len());
+    Ok(true)
+}
+
+/// Options chosen on the command line for a commit run
+pub struct RunOptions {
+    pub use_emoji: bool,
+    pub push: bool,
+    /// Number of messages generated at once; 1 disables the candidate picker
+    pub candidates: usize,
+    pub mode: Mode,
+    /// Print the result as JSON (non-interactive modes only)
+    pub json: bool,
+    /// Use the message saved by a failed or cancelled run instead of generating one
+    pub reuse: bool,
+    /// Stage modified and deleted tracked files first, like `git commit -a`
+    pub stage_all: bool,
+    /// Replace the last commit's message (and add anything staged) instead of committing
+    pub amend: bool,
+}
+
+/// Commits the index, or rewrites the last commit when amending
+fn commit(backend: &dyn git::GitBackend, message: &str, amend: bool) -> Result<()> {
+    if amend {
+        backend.amend(message)
+    } else {
+        backend.commit(message)
+    }
+}
+
+/// Generation attempts made without a human before giving up on a valid message
+const MAX_ATTEMPTS: usize = 3;
+
+/// Result printed with `--json`
+#[derive(Serialize, Default)]
+struct JsonOutput<'a> {
+    message: Option<&'a str>,
+    subject: Option<&'a str>,
+    body: Option<&'a str>,
+    valid: bool,
+    committed: bool,
+    pushed: bool,
+    /// Why the run failed, when it did
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Fails the run; with `--json` the output is still printed, with the reason
+fn fail(options: &RunOptions, mut output: JsonOutput, error: anyhow::Error) -> Result<()> {
+    if options.json {
+        output.error = Some(error.to_string());
+        println!("{}", serde_json::to_string_pretty(&output)?);
+    }
+    Err(error)
+}
+
+/// Builds the prompt from the staged changes (plus the last commit when amending),
+/// or returns `None` when there is nothing to describe
+pub fn build_prompt(
+    backend: &dyn git::GitBackend,
+    use_emoji: bool,
+    amend: bool,
+    configuration: &config::Config,
+) -> Result<Option<String>> {
+    let full_diff = if amend {
+        backend.amend_diff()?
+    } else {
+        backend.staged_diff()?
+    };
+    if full_diff.trim().is_empty() {
+        return Ok(None);
+    }
+
+    let project_context = project::get_project_context();
+    let style_context = style::get_style_context(backend, &full_diff, &configuration.commit);
+    let prompt = |diff: &str| {
+        prompts::generate_commit_prompt(
+            diff,
+            &project_context,
+            &style_context,
+            use_emoji,
+            &configuration.commit,
+        )
+    };
+
+    // The diff gets whatever the rest of the prompt leaves of the context window
+    let diff_budget = budget::diff_budget(budget::context_window(configuration), &prompt(""));
+    let diff = git::filter_diff(
+        &full_diff,
+        diff_budget,
+        &git::IgnoreRules::load(&configuration.ignore),
+    );
+    Ok(Some(prompt(&diff)))
+}
+
+/// Returns the reason when `error` means the model answered something other than
+/// a conventional commit, as opposed to a failed request
+fn invalid_output(error: &anyhow::Error) -> Option<&ParseError> {
+    error.downcast_ref::<ParseError>()
+}
+
+fn warn_invalid(error: &ParseError, attempt: usize) {
+    eprintln!(
+        "{} {} (attempt {attempt}/{MAX_ATTEMPTS})",
+        "⚠️  Not a conventional commit:".yellow(),
+        error
+    );
+}
+
+/// Generates messages until one is a valid conventional commit.
+/// Returns `None` when none of the attempts was; failed requests are returned as errors.
+pub fn generate_valid_message(
+    prompt: &str,
+    configuration: &config::Config,
+    use_emoji: bool,
+) -> Result<Option<String>> {
+    retry_until_valid(&configuration.commit, || {
+        ai::generate_commit_message(prompt, configuration, use_emoji)
+    })
+}
+
+/// Calls `generate` until it returns a message `ai::validate_commit_message` accepts
+fn retry_until_valid(
+    commit_config: &config::CommitConfig,
+    mut generate: impl FnMut() -> Result<String>,
+) -> Result<Option<String>> {
+    for attempt in 1..=MAX_ATTEMPTS {
+        match generate() {
+            Ok(message) => match ai::validate_commit_message(&message, commit_config) {
+                Ok(_) => return Ok(Some(message)),
+                Err(e) => warn_invalid(&e, attempt),
+            },
+            Err(e) => match invalid_output(&e) {
+                Some(reason) => warn_invalid(reason, attempt),
+                None => return Err(e),
+            },
+        }
+    }
+    Ok(None)
+}
+
+/// Runs without prompting, for scripts, hooks and CI
+fn run_non_interactive(configuration: &config::Config, options: &RunOptions) -> Result<()> {
+    let backend = git::open_backend(&configuration.git)?;
+    if options.stage_all {
+        stage_tracked_changes(backend.as_ref())?;
+    }
+    let Some(prompt) = build_prompt(
+        backend.as_ref(),
+        options.use_emoji,
+        options.amend,
+        configuration,
+    )?
+    else {
+        eprintln!("{}", "⚠️  No changes to commit.".yellow());
+        if options.json {
+            println!("{}", serde_json::to_string_pretty(&JsonOutput::default())?);
+        }
+        return Ok(());
+    };
+
+    let saved = if options.reuse {
+        git::load_saved_message()
+    } else {
+        None
+    };
+    let (message, valid) = match saved {
+        Some(message) => {
+            let valid = ai::validate_commit_message(&message, &configuration.commit).is_ok();
+            (message, valid)
+        }
+        None => {
+            if options.reuse {
+                eprintln!(
+                    "{}",
+                    "⚠️  No saved message to reuse, generating a new one.".yellow()
+                );
+            }
+            match generate_valid_message(&prompt, configuration, options.use_emoji)? {
+                Some(message) => (message, true),
+                None => {
+                    return fail(
+                        options,
+                        JsonOutput::default(),
+                        anyhow!(
+                            "No valid commit message after {MAX_ATTEMPTS} attempts, nothing was committed"
+                        ),
+                    );
+                }
+            }
+        }
+    };
+    let mut output = JsonOutput {
+        message: Some(&message),
+        valid,
+        ..Default::default()
+    };
+    match message.split_once('\n') {
+        Some((subject, body)) => {
+            output.subject = Some(subject);
+            output.body = Some(body.trim()).filter(|body| !body.is_empty());
+        }
+        None => output.subject = Some(&message),
+    }
+
+    if options.mode == Mode::Commit {
+        if !valid {
+            return fail(
+                options,
+                output,
+                anyhow!(
+                    "The saved message is not a valid conventional commit, nothing was committed"
+                ),
+            );
+        }
+
+        if let Err(e) = commit(backend.as_ref(), &message, options.amend) {
+            let _ = git::save_message(&message);
+            return fail(options, output, e);
+        }
+        git::clear_saved_message();
+        output.committed = true;
+
+        if options.push {
+            if let Err(e) = git::execute_git_push() {
+                return fail(options, output, e);
+            }
+            output.pushed = true;
+        }
+    } else if options.push {
+        eprintln!(
+            "{}",
+            "⚠️  --push ignored, nothing is committed in dry-run mode.".yellow()
+        );
+    }
+
+    if !valid {
+        return fail(
+            options,
+            output,
+            anyhow!("The saved message is not a valid conventional commit"),
+        );
+    }
+    if options.json {
+        println!("{}", serde_json::to_string_pretty(&output)?);
+    } else if output.committed {
+        println!("{}", "✅ Commit successful!".bold().green());
+        println!("📄 Message: {}", message.white());
+        if output.pushed {
+            println!("{}", "✅ Push successful!".bold().green());
+        }
+    } else {
+        // Plain message only, so the output can be piped into `git commit -F -`
+        println!("{message}");
+    }
+    Ok(())
+}
+
+/// Generates another batch of candidates with a spinner showing progress
+fn generate_more_candidates(
+    prompt: &str,
+    configuration: &config::Config,
+    options: &RunOptions,
+    candidates: &mut Vec<String>,
+) -> Result<()> {
+    let mut spinner: Option<spinners::Spinner> = None;
+    let result = ai::generate_candidates(
+        prompt,
+        configuration,
+        options.use_emoji,
+        options.candidates,
+        candidates,
+        &mut |index| {
+            if let Some(mut previous) = spinner.take() {
+                previous.stop();
+                println!();
+            }
+            spinner = Some(terminal::show_spinner(&format!(
+                "Generating candidate {index}/{}...",
+                options.candidates
+            )));
+        },
+    );
+    if let Some(mut last) = spinner {
+        last.stop();
+        println!();
+    }
+
+    let new_candidates = result?;
+    if new_candidates.is_empty() {
+        println!("{}", "⚠️  No new distinct message was generated.".yellow());
+    }
+    candidates.extend(new_candidates);
+    Ok(())
+}
+
+/// Lets the user pick one of several generated messages, generating more on request.
+/// Returns `None` when the user cancels.
+fn choose_candidate(
+    prompt: &str,
+    configuration: &config::Config,
+    options: &RunOptions,
+    candidates: &mut Vec<String>,
+) -> Result<Option<String>> {
+    if candidates.is_empty() {
+        generate_more_candidates(prompt, configuration, options, candidates)?;
+    }
+
+    loop {
+        terminal::clear_terminal();
+        println!();
+
+        // Show the subjects side by side, bodies are displayed once a candidate is picked
+        let mut items: Vec<String> = candidates
+            .iter()
+            .map(|candidate| candidate.lines().next().unwrap_or("").to_string())
+            .collect();
+        items.push("➕ Generate more".to_string());
+        items.push("❌ Cancel".to_string());
+
+        let selection = terminal::show_selection_menu(
+            items.iter().map(String::as_str).collect(),
+            "Pick a commit message",
+        )?;
+
+        if selection < candidates.len() {
+            return Ok(Some(candidates[selection].clone()));
+        } else if selection == candidates.len() {
+            generate_more_candidates(prompt, configuration, options, candidates)?;
+        } else {
+            return Ok(None);
+        }
+    }
+}
+
+pub fn run(configuration: &config::Config, options: &RunOptions) -> Result<()> {
+    if options.mode != Mode::Interactive {
+        return run_non_interactive(configuration, options);
+    }
+
+    let use_emoji = options.use_emoji;
+    let push = options.push;
     terminal::clear_terminal();
-    let configuration = config::load_config();
-
-    // Get system specs once at the beginning
-    let system_specs = system::get_system_info().unwrap_or(system::SystemSpecs {
-        cpu_cores: None,
-        cpu_model: None,
-        memory_gb: None,
-        gpu_model: None,
-        os_info: None,
-        performance_level: system::PerformanceLevel::Medium,
-    });
 
     terminal::print_header(
         &format!("Using model: {}", configuration.model),
         Some(console::Color::Yellow),
     );
 
-    let diff = git::get_git_diff(&system_specs);
-    if diff.trim().is_empty() {
-        println!("{}", "⚠️  No changes to commit.".yellow());
-        return Ok(());
+    let backend = git::open_backend(&configuration.git)?;
+    if options.stage_all {
+        stage_tracked_changes(backend.as_ref())?;
     }
 
-    let project_context = project::get_project_context();
+    if options.amend {
+        if let Some(last) = backend.history(1)?.first() {
+            let subject = last.message.lines().next().unwrap_or("");
+            println!("✏️  Amending: {}", subject.white());
+            println!();
+        }
+    }
 
-    let prompt = prompts::generate_commit_prompt(&diff, &project_context, use_emoji);
+    let mut prompt = build_prompt(backend.as_ref(), use_emoji, options.amend, configuration)?;
+    if prompt.is_none() && !options.amend && stage_interactively(backend.as_ref(), configuration)? {
+        prompt = build_prompt(backend.as_ref(), use_emoji, false, configuration)?;
+    }
+    let Some(prompt) = prompt else {
+        println!("{}", "⚠️  No changes to commit.".yellow());
+        return Ok(());
+    };
 
     let mut should_regenerate = true;
     let mut clean_msg = String::new();
+    let mut failed_attempts = 0;
+    let mut retry_warning: Option<(ParseError, usize)> = None;
+    let mut candidates: Vec<String> = Vec::new();
+
+    // Offer the message a previous run could not commit before generating a new one
+    if let Some(saved) = git::load_saved_message() {
+        let reuse = options.reuse || {
+            print_commit_message("Saved message from a previous run:", &saved);
+            println!();
+            let saved_options = vec!["♻️ Reuse saved message", "🔄 Generate a new one"];
+            terminal::show_selection_menu(saved_options, "This message was not committed")? == 0
+        };
+        if reuse {
+            clean_msg = saved;
+            should_regenerate = false;
+        } else {
+            git::clear_saved_message();
+        }
+    } else if options.reuse {
+        println!(
+            "{}",
+            "⚠️  No saved message to reuse, generating a new one.".yellow()
+        );
+    }
 
     terminal::print_header(&format!("> Commayte (v{VERSION})"), None);
 
     loop {
         if should_regenerate {
             terminal::clear_terminal();
-            let mut sp = terminal::show_spinner("Generating commit message...");
-            clean_msg = ai::generate_commit_message(&prompt, &configuration, use_emoji)?;
-            sp.stop();
+            if let Some((reason, attempt)) = retry_warning.take() {
+                warn_invalid(&reason, attempt);
+            }
+
+            if options.candidates > 1 {
+                match choose_candidate(&prompt, configuration, options, &mut candidates)? {
+                    Some(msg) => clean_msg = msg,
+                    None => {
+                        terminal::clear_terminal();
+                        println!("{}", "❌ Cancelled by user".red());
+                        return Ok(());
+                    }
+                }
+            } else if configuration.stream {
+                println!(
+                    "{}",
+                    "Generating commit message... (Ctrl-C to cancel)".dimmed()
+                );
+                let _interrupts = terminal::catch_interrupts();
+                let result = ai::stream_commit_message(
+                    &prompt,
+                    configuration,
+                    use_emoji,
+                    &mut terminal::print_stream_token,
+                    &terminal::interrupted,
+                );
+                println!();
+
+                match result {
+                    Ok(msg) => {
+                        clean_msg = msg;
+                        failed_attempts = 0;
+                    }
+                    Err(e) if e.to_string().contains("Generation cancelled by user") => {
+                        println!("{}", "⏹️  Generation cancelled".yellow());
+                        if clean_msg.is_empty() {
+                            return Ok(());
+                        }
+                        // Fall back to the previous suggestion
+                    }
+                    Err(e) => {
+                        let Some(reason) = invalid_output(&e) else {
+                            return Err(e);
+                        };
+                        failed_attempts += 1;
+                        if failed_attempts < MAX_ATTEMPTS {
+                            // Shown once the terminal is cleared for the next attempt
+                            retry_warning = Some((reason.clone(), failed_attempts));
+                            continue;
+                        }
+                        warn_invalid(reason, failed_attempts);
+                        failed_attempts = 0;
+                        if clean_msg.is_empty() {
+                            return Err(anyhow!(
+                                "No valid commit message after {MAX_ATTEMPTS} attempts"
+                            ));
+                        }
+                        // Fall back to the previous suggestion
+                    }
+                }
+            } else {
+                let mut sp = terminal::show_spinner("Generating commit message...");
+                let generated = generate_valid_message(&prompt, configuration, use_emoji);
+                sp.stop();
+                match generated? {
+                    Some(msg) => clean_msg = msg,
+                    None if clean_msg.is_empty() => {
+                        return Err(anyhow!(
+                            "No valid commit message after {MAX_ATTEMPTS} attempts"
+                        ));
+                    }
+                    // Keep the previous suggestion
+                    None => {}
+                }
+            }
             println!();
         }
 
         terminal::clear_terminal();
 
         println!();
-        println!(
-            "📝 {} {}",
-            "Generated commit message:".bold().green(),
-            clean_msg.bold().white()
-        );
+        print_commit_message("Generated commit message:", &clean_msg);
         println!();
 
-        let options = vec![
+        // With candidates, "regenerate" goes back to the list instead of discarding it
+        let regenerate_option = if options.candidates > 1 {
+            "🔄 Choose another candidate"
+        } else {
+            "🔄 Regenerate message"
+        };
+        let menu_options = vec![
             "✅ Accept and commit",
             "✏️ Edit message",
-            "🔄 Regenerate message",
+            regenerate_option,
             "❌ Cancel",
         ];
-        let selection = terminal::show_selection_menu(options, "What would you like to do?")?;
+        let selection = terminal::show_selection_menu(menu_options, "What would you like to do?")?;
 
         let final_message = match selection {
             0 => clean_msg,
@@ -82,7 +631,10 @@ pub fn run(use_emoji: bool, push: bool) -> Result<()> {
                     terminal::clear_terminal();
 
                     // Use custom in-terminal editing
-                    let edited_msg = match terminal::edit_in_terminal(&current_message) {
+                    let edited_msg = match terminal::edit_commit_message(
+                        &current_message,
+                        configuration.commit.body,
+                    ) {
                         Ok(msg) => msg,
                         Err(e) => {
                             if e.to_string().contains("Editing cancelled by user") {
@@ -96,11 +648,11 @@ pub fn run(use_emoji: bool, push: bool) -> Result<()> {
 
                     println!();
 
-                    println!(
-                        "📝 {} {}",
-                        "Edited commit message:".bold().green(),
-                        edited_msg.bold().white()
-                    );
+                    print_commit_message("Edited commit message:", &edited_msg);
+                    if let Err(e) = ai::validate_commit_message(&edited_msg, &configuration.commit)
+                    {
+                        println!("{} {}", "⚠️  Not a conventional commit:".yellow(), e);
+                    }
                     println!();
 
                     let confirm_options = vec!["✅ Use this message", "✏️ Edit again", "❌ Cancel"];
@@ -123,6 +675,7 @@ pub fn run(use_emoji: bool, push: bool) -> Result<()> {
                             // User cancelled
                             terminal::clear_terminal();
                             println!("{}", "❌ Cancelled by user".red());
+                            keep_message(&edited_msg);
                             return Ok(());
                         }
                         _ => unreachable!(),
@@ -146,80 +699,155 @@ pub fn run(use_emoji: bool, push: bool) -> Result<()> {
             3 => {
                 terminal::clear_terminal();
                 println!("{}", "❌ Cancelled by user".red());
+                keep_message(&clean_msg);
                 return Ok(());
             }
             _ => unreachable!(),
         };
 
-        // Commit with the final message (either original, edited, or regenerated)
-        terminal::clear_terminal();
+        // Commit with the final message (either original, edited, or regenerated),
+        // retrying on request so a rejected commit doesn't cost the message
+        loop {
+            terminal::clear_terminal();
 
-        let mut commit_sp = terminal::show_spinner("Committing changes...");
+            let mut commit_sp = terminal::show_spinner("Committing changes...");
 
-        let commit_result = git::execute_git_commit(&final_message);
+            let commit_result = commit(backend.as_ref(), &final_message, options.amend);
 
-        commit_sp.stop();
-        terminal::clear_terminal();
+            commit_sp.stop();
+            terminal::clear_terminal();
 
-        match commit_result {
-            Ok(status) if status.success() => {
-                println!("{}", "✅ Commit successful!".bold().green());
-                println!("📄 Message: {}", final_message.white());
-                
-                if push {
-                    println!("🚀 Pushing to current branch...");
-                    let mut push_sp = terminal::show_spinner("Pushing changes...");
-                    let push_result = git::execute_git_push();
-                    push_sp.stop();
-                    
-                    match push_result {
-                        Ok(status) if status.success() => {
-                            println!("{}", "✅ Push successful!".bold().green());
-                        }
-                        Ok(status) => {
-                            println!("{}", "⚠️ Push completed with warnings.".yellow());
-                            println!("🔍 Exit code: {}", status.code().unwrap_or(-1));
-                        }
-                        Err(_) => {
-                            println!("{}", "❌ Git push failed.".bold().red());
+            match commit_result {
+                Ok(()) => {
+                    git::clear_saved_message();
+                    println!("{}", "✅ Commit successful!".bold().green());
+                    println!("📄 Message: {}", final_message.white());
+
+                    if push {
+                        println!("🚀 Pushing to current branch...");
+                        let mut push_sp = terminal::show_spinner("Pushing changes...");
+                        let push_result = git::execute_git_push();
+                        push_sp.stop();
+                        println!();
+
+                        match push_result {
+                            Ok(()) => {
+                                println!("{}", "✅ Push successful!".bold().green());
+                            }
+                            Err(e) => {
+                                print_git_failure(
+                                    "Git push failed",
+                                    &e,
+                                    git::FailureKind::classify(&e.to_string()),
+                                );
+                            }
                         }
                     }
+                    return Ok(());
                 }
-                break;
-            }
-            Ok(status) => {
-                println!("{}", "⚠️ Commit completed with warnings.".yellow());
-                println!("📄 Message: {}", final_message.white());
-                println!("🔍 Exit code: {}", status.code().unwrap_or(-1));
-                
-                if push {
-                    println!("🚀 Pushing to current branch...");
-                    let mut push_sp = terminal::show_spinner("Pushing changes...");
-                    let push_result = git::execute_git_push();
-                    push_sp.stop();
-                    
-                    match push_result {
-                        Ok(status) if status.success() => {
-                            println!("{}", "✅ Push successful!".bold().green());
-                        }
-                        Ok(status) => {
-                            println!("{}", "⚠️ Push completed with warnings.".yellow());
-                            println!("🔍 Exit code: {}", status.code().unwrap_or(-1));
+                Err(e) => {
+                    // Saved right away so the message survives even if the terminal is closed
+                    let _ = git::save_message(&final_message);
+                    print_git_failure(
+                        "Git commit failed",
+                        &e,
+                        git::FailureKind::classify_commit(&e.to_string()),
+                    );
+                    println!();
+                    print_commit_message("Message:", &final_message);
+                    println!();
+
+                    let failure_options =
+                        vec!["🔁 Retry commit", "✏️ Back to the message", "❌ Cancel"];
+                    match terminal::show_selection_menu(
+                        failure_options,
+                        "What would you like to do?",
+                    )? {
+                        0 => continue,
+                        1 => {
+                            clean_msg = final_message;
+                            should_regenerate = false;
+                            break;
                         }
-                        Err(_) => {
-                            println!("{}", "❌ Git push failed.".bold().red());
+                        2 => {
+                            terminal::clear_terminal();
+                            println!("{}", "❌ Cancelled by user".red());
+                            keep_message(&final_message);
+                            return Ok(());
                         }
+                        _ => unreachable!(),
                     }
                 }
-                break;
-            }
-            Err(_) => {
-                println!("{}", "❌ Git commit failed.".bold().red());
-                println!("📄 Message: {}", final_message.white());
-                break;
             }
         }
     }
+}
 
-    Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs `retry_until_valid` on canned model output, returning the result and the calls made
+    fn retry(outputs: &[Result<&str, ParseError>]) -> (Result<Option<String>>, usize) {
+        let mut calls = 0;
+        let result = retry_until_valid(&config::CommitConfig::default(), || {
+            let output = outputs[calls].clone();
+            calls += 1;
+            output.map(str::to_string).map_err(anyhow::Error::from)
+        });
+        (result, calls)
+    }
+
+    #[test]
+    fn accepts_a_gitmoji_message_at_once() {
+        for message in [
+            "🧪 test: cover the parser",
+            "⬆️ build: bump serde",
+            "⏪️ revert: undo x",
+        ] {
+            let (result, calls) = retry(&[Ok(message)]);
+            assert_eq!(result.unwrap().as_deref(), Some(message));
+            assert_eq!(calls, 1);
+        }
+    }
+
+    #[test]
+    fn retries_what_the_validator_rejects() {
+        let (result, calls) = retry(&[
+            Err(ParseError::MissingColon),
+            Ok("feat: x\nno blank line"),
+            Ok("fix: handle empty diffs"),
+        ]);
+        assert_eq!(result.unwrap().as_deref(), Some("fix: handle empty diffs"));
+        assert_eq!(calls, 3);
+
+        let (result, calls) = retry(&[Ok("nonsense"), Ok("wip: x"), Err(ParseError::Empty)]);
+        assert!(result.unwrap().is_none());
+        assert_eq!(calls, MAX_ATTEMPTS);
+    }
+
+    #[test]
+    fn failed_requests_are_not_retried() {
+        let mut calls = 0;
+        let result = retry_until_valid(&config::CommitConfig::default(), || {
+            calls += 1;
+            Err(anyhow!("connection refused"))
+        });
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn json_output_has_an_error_only_on_failure() {
+        let output = JsonOutput::default();
+        assert!(!serde_json::to_string(&output).unwrap().contains("error"));
+
+        let output = JsonOutput {
+            error: Some("commit failed".to_string()),
+            ..Default::default()
+        };
+        assert!(serde_json::to_string(&output)
+            .unwrap()
+            .contains(r#""error":"commit failed""#));
+    }
 }
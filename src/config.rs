@@ -1,21 +1,391 @@
+use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// LLM backend used to generate commit messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Provider {
+    /// Local Ollama server (`/api/generate`)
+    #[default]
+    #[serde(rename = "ollama")]
+    Ollama,
+    /// Any server speaking the OpenAI `/v1/chat/completions` API
+    /// (llama.cpp server, vLLM, LM Studio, ...)
+    #[serde(rename = "openai")]
+    OpenAi,
+}
+
+/// Implementation used to talk to the repository
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum GitBackendKind {
+    /// The `git` command when it is on PATH, libgit2 otherwise
+    #[default]
+    #[serde(rename = "auto")]
+    Auto,
+    /// Always run the `git` command
+    #[serde(rename = "cli")]
+    Cli,
+    /// Always use the bundled libgit2 (no hooks, no commit signing)
+    #[serde(rename = "libgit2")]
+    Libgit2,
+}
 
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
     pub model: String,
+    pub provider: Provider,
+    /// Show tokens live while the message is generated
+    pub stream: bool,
+    /// Context window of the model in tokens, used to size the diff in the prompt;
+    /// asked from Ollama when unset
+    pub context_window: Option<u32>,
+    pub commit: CommitConfig,
+    pub git: GitConfig,
+    pub lint: LintConfig,
+    pub ignore: IgnoreConfig,
+    pub ollama: OllamaConfig,
+    pub openai: OpenAiConfig,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             model: "mistral".to_string(),
+            provider: Provider::default(),
+            stream: true,
+            context_window: None,
+            commit: CommitConfig::default(),
+            git: GitConfig::default(),
+            lint: LintConfig::default(),
+            ignore: IgnoreConfig::default(),
+            ollama: OllamaConfig::default(),
+            openai: OpenAiConfig::default(),
+        }
+    }
+}
+
+/// Shape of the generated commit messages (`[commit]` table)
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CommitConfig {
+    /// Generate a body (and optional footers) after the subject line
+    pub body: bool,
+    /// Allowed scopes; empty means any scope is accepted
+    pub scopes: Vec<String>,
+    /// Allowed commit types, listed in the prompt with their descriptions
+    pub types: Vec<CommitType>,
+    /// Past commits shown to the model as examples of the repository's style; 0 disables
+    pub examples: usize,
+}
+
+impl Default for CommitConfig {
+    fn default() -> Self {
+        let types = [
+            ("feat", "new features"),
+            ("fix", "bug fixes"),
+            ("chore", "maintenance/config"),
+            ("docs", "documentation"),
+            ("style", "formatting"),
+            ("refactor", "code restructuring"),
+            ("test", "adding/updating tests"),
+            ("perf", "performance improvements"),
+            ("build", "build system or dependencies"),
+            ("ci", "continuous integration"),
+            ("revert", "reverting a previous commit"),
+        ];
+
+        Self {
+            body: false,
+            examples: 5,
+            scopes: Vec::new(),
+            types: types
+                .iter()
+                .map(|(name, description)| CommitType {
+                    name: name.to_string(),
+                    description: description.to_string(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl CommitConfig {
+    /// Names of the allowed commit types
+    pub fn type_names(&self) -> Vec<&str> {
+        self.types.iter().map(|t| t.name.as_str()).collect()
+    }
+
+    /// Allowed scopes, empty when any scope is accepted
+    pub fn scope_names(&self) -> Vec<&str> {
+        self.scopes.iter().map(String::as_str).collect()
+    }
+}
+
+/// A commit type entry (`[[commit.types]]`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitType {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+/// Repository access settings (`[git]` table)
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GitConfig {
+    pub backend: GitBackendKind,
+}
+
+/// Casing required for the first letter of the subject
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SubjectCase {
+    /// `feat: add login`
+    #[default]
+    #[serde(rename = "lower")]
+    Lower,
+    /// `feat: Add login`
+    #[serde(rename = "sentence")]
+    Sentence,
+    /// No requirement
+    #[serde(rename = "any")]
+    Any,
+}
+
+/// Rules checked by `commayte lint` (`[lint]` table)
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LintConfig {
+    pub header_max_length: usize,
+    pub body_max_line_length: usize,
+    pub subject_case: SubjectCase,
+    /// Names of rules to skip, e.g. `["body-max-line-length"]`
+    pub disabled: Vec<String>,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        Self {
+            header_max_length: 72,
+            body_max_line_length: 100,
+            subject_case: SubjectCase::default(),
+            disabled: Vec::new(),
+        }
+    }
+}
+
+/// Extra rules for files left out of the prompt (`[ignore]` table), in `.gitignore` syntax
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IgnoreConfig {
+    /// Patterns to leave out, on top of the defaults and `.commayteignore`
+    pub exclude: Vec<String>,
+    /// Patterns to keep even when another rule leaves them out
+    pub include: Vec<String>,
+}
+
+/// Settings for the Ollama provider (`[ollama]` table)
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OllamaConfig {
+    /// Base URL of the Ollama server
+    pub host: String,
+    /// Request timeout in seconds
+    pub timeout_secs: u64,
+    /// How long Ollama keeps the model loaded after the request (e.g. "5m", "-1")
+    pub keep_alive: Option<String>,
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+    /// Context window size in tokens
+    pub num_ctx: Option<u32>,
+    /// Fixed seed for reproducible output
+    pub seed: Option<i64>,
+}
+
+impl Default for OllamaConfig {
+    fn default() -> Self {
+        Self {
+            host: "http://localhost:11434".to_string(),
+            timeout_secs: 45,
+            keep_alive: None,
+            temperature: None,
+            top_p: None,
+            num_ctx: None,
+            seed: None,
+        }
+    }
+}
+
+/// Settings for the OpenAI-compatible provider (`[openai]` table)
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OpenAiConfig {
+    /// Base URL of the API, without the `/chat/completions` suffix
+    pub base_url: String,
+    /// Optional bearer token sent in the `Authorization` header
+    pub api_key: Option<String>,
+    /// Request timeout in seconds
+    pub timeout_secs: u64,
+}
+
+impl Default for OpenAiConfig {
+    fn default() -> Self {
+        Self {
+            base_url: "http://localhost:8080/v1".to_string(),
+            api_key: None,
+            timeout_secs: 45,
+        }
+    }
+}
+
+/// Name of the repository-local configuration file
+const REPO_CONFIG_FILE: &str = ".commayte.toml";
+
+/// Prefix of the environment variables overriding configuration keys
+const ENV_PREFIX: &str = "COMMAYTE_";
+
+/// Type of the value stored under a configuration key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueKind {
+    String,
+    Integer,
+    Float,
+    Boolean,
+    /// An array of strings (comma-separated when given as text)
+    StringList,
+    /// An array of `{ name, description }` tables
+    TypeList,
+    /// A string restricted to a fixed set of values
+    Enum(&'static [&'static str]),
+}
+
+/// Every scalar key understood by Commayte, in dotted form
+pub const KEYS: &[(&str, ValueKind)] = &[
+    ("model", ValueKind::String),
+    ("provider", ValueKind::Enum(&["ollama", "openai"])),
+    ("stream", ValueKind::Boolean),
+    ("context_window", ValueKind::Integer),
+    ("commit.body", ValueKind::Boolean),
+    ("commit.scopes", ValueKind::StringList),
+    ("commit.types", ValueKind::TypeList),
+    ("commit.examples", ValueKind::Integer),
+    ("git.backend", ValueKind::Enum(&["auto", "cli", "libgit2"])),
+    ("lint.header_max_length", ValueKind::Integer),
+    ("lint.body_max_line_length", ValueKind::Integer),
+    (
+        "lint.subject_case",
+        ValueKind::Enum(&["lower", "sentence", "any"]),
+    ),
+    ("lint.disabled", ValueKind::StringList),
+    ("ignore.exclude", ValueKind::StringList),
+    ("ignore.include", ValueKind::StringList),
+    ("ollama.host", ValueKind::String),
+    ("ollama.timeout_secs", ValueKind::Integer),
+    ("ollama.keep_alive", ValueKind::String),
+    ("ollama.temperature", ValueKind::Float),
+    ("ollama.top_p", ValueKind::Float),
+    ("ollama.num_ctx", ValueKind::Integer),
+    ("ollama.seed", ValueKind::Integer),
+    ("openai.base_url", ValueKind::String),
+    ("openai.api_key", ValueKind::String),
+    ("openai.timeout_secs", ValueKind::Integer),
+];
+
+/// Returns the kind of a known configuration key
+pub fn key_kind(key: &str) -> Option<ValueKind> {
+    KEYS.iter().find(|(k, _)| *k == key).map(|(_, kind)| *kind)
+}
+
+/// Name of the environment variable overriding `key` (e.g. `COMMAYTE_OLLAMA_HOST`)
+pub fn env_var_name(key: &str) -> String {
+    format!("{ENV_PREFIX}{}", key.replace('.', "_").to_uppercase())
+}
+
+/// Parses a raw string (from the environment or the command line) into a typed TOML value
+pub fn parse_value(key: &str, raw: &str) -> Result<toml::Value> {
+    let kind = key_kind(key).ok_or_else(|| anyhow!("Unknown configuration key '{key}'"))?;
+
+    let value = match kind {
+        ValueKind::String => toml::Value::String(raw.to_string()),
+        ValueKind::Integer => toml::Value::Integer(
+            raw.trim()
+                .parse()
+                .map_err(|_| anyhow!("'{key}' expects an integer, got '{raw}'"))?,
+        ),
+        ValueKind::Float => toml::Value::Float(
+            raw.trim()
+                .parse()
+                .map_err(|_| anyhow!("'{key}' expects a number, got '{raw}'"))?,
+        ),
+        ValueKind::Boolean => toml::Value::Boolean(
+            raw.trim()
+                .parse()
+                .map_err(|_| anyhow!("'{key}' expects true or false, got '{raw}'"))?,
+        ),
+        ValueKind::StringList => toml::Value::Array(
+            raw.split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(|item| toml::Value::String(item.to_string()))
+                .collect(),
+        ),
+        ValueKind::TypeList => {
+            return Err(anyhow!(
+                "'{key}' is a list of tables, edit it with 'commayte config edit'"
+            ))
+        }
+        ValueKind::Enum(allowed) => {
+            if !allowed.contains(&raw) {
+                return Err(anyhow!(
+                    "'{key}' expects one of {}, got '{raw}'",
+                    allowed.join(", ")
+                ));
+            }
+            toml::Value::String(raw.to_string())
+        }
+    };
+
+    Ok(value)
+}
+
+/// Sets a dotted key inside a TOML table, creating intermediate tables as needed
+pub fn set_dotted(table: &mut toml::Table, key: &str, value: toml::Value) {
+    let mut current = table;
+    let mut parts = key.split('.').peekable();
+
+    while let Some(part) = parts.next() {
+        if parts.peek().is_none() {
+            current.insert(part.to_string(), value);
+            return;
+        }
+
+        let entry = current
+            .entry(part.to_string())
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+        if !entry.is_table() {
+            *entry = toml::Value::Table(toml::Table::new());
         }
+        current = entry.as_table_mut().unwrap();
     }
 }
 
-fn get_config_path() -> PathBuf {
+/// Recursively merges `overlay` into `base`, overlay values winning
+fn merge_tables(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base_table)), toml::Value::Table(overlay_table)) => {
+                merge_tables(base_table, overlay_table);
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+pub fn get_config_path() -> PathBuf {
     // Always use ~/.config/commayte to match the install script
     dirs::home_dir()
         .unwrap_or_else(|| PathBuf::from("."))
@@ -24,22 +394,470 @@ fn get_config_path() -> PathBuf {
         .join("config.toml")
 }
 
-pub fn load_config() -> Config {
-    let config_path = get_config_path();
+/// Walks up from the current directory to the directory containing `.git`
+pub fn find_repo_root() -> Option<PathBuf> {
+    let mut dir = std::env::current_dir().ok()?;
 
-    match fs::read_to_string(&config_path) {
-        Ok(config_content) => match toml::from_str(&config_content) {
-            Ok(config) => config,
-            Err(e) => {
-                eprintln!("Warning: Failed to parse config file: {e}");
-                eprintln!("Using default configuration");
-                Config::default()
-            }
-        },
+    loop {
+        // `.git` is a file for worktrees and submodules
+        if dir.join(".git").exists() {
+            return Some(dir);
+        }
+        if !dir.pop() {
+            return None;
+        }
+    }
+}
+
+/// Finds the nearest `.commayte.toml`, walking up from the current directory to the git root
+pub fn find_repo_config_path() -> Option<PathBuf> {
+    let mut dir = std::env::current_dir().ok()?;
+
+    loop {
+        let candidate = dir.join(REPO_CONFIG_FILE);
+        if candidate.is_file() {
+            return Some(candidate);
+        }
+
+        // Stop at the repository root (`.git` is a file for worktrees and submodules)
+        if dir.join(".git").exists() || !dir.pop() {
+            return None;
+        }
+    }
+}
+
+/// Path where a repo-local config file is (or would be) stored
+pub fn repo_config_path() -> Option<PathBuf> {
+    find_repo_config_path().or_else(|| find_repo_root().map(|root| root.join(REPO_CONFIG_FILE)))
+}
+
+/// Reads a configuration file as a raw TOML table, warning if it is malformed
+fn read_config_layer(path: &Path) -> Option<toml::Table> {
+    let content = fs::read_to_string(path).ok()?;
+
+    match toml::from_str(&content) {
+        Ok(table) => Some(table),
         Err(e) => {
-            eprintln!("Warning: Could not read config file at {config_path:?}: {e}",);
-            eprintln!("Using default configuration");
-            Config::default()
+            eprintln!("Warning: Failed to parse config file {path:?}: {e}");
+            eprintln!("Ignoring this file (run 'commayte config validate' for details)");
+            None
         }
     }
 }
+
+/// Layer a configuration value was resolved from
+#[derive(Debug, Clone)]
+pub enum ConfigSource {
+    Default,
+    Global(PathBuf),
+    Repo(PathBuf),
+    Env(String),
+    Cli,
+}
+
+impl std::fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigSource::Default => write!(f, "default"),
+            ConfigSource::Global(path) => write!(f, "global:{}", path.display()),
+            ConfigSource::Repo(path) => write!(f, "repo:{}", path.display()),
+            ConfigSource::Env(var) => write!(f, "env:{var}"),
+            ConfigSource::Cli => write!(f, "command line"),
+        }
+    }
+}
+
+/// The merged configuration along with the raw values and where each one came from
+pub struct ResolvedConfig {
+    pub config: Config,
+    pub values: toml::Table,
+    pub origins: BTreeMap<String, ConfigSource>,
+}
+
+/// Writes the `f32` settings back as the shortest decimal, `0.3` rather than `0.30000001192092896`
+fn shorten_floats(table: &mut toml::Table) {
+    for (_, value) in table.iter_mut() {
+        match value {
+            toml::Value::Float(number) => {
+                *number = (*number as f32).to_string().parse().unwrap_or(*number);
+            }
+            toml::Value::Table(inner) => shorten_floats(inner),
+            _ => {}
+        }
+    }
+}
+
+impl ResolvedConfig {
+    /// Values of the configuration that actually runs, as opposed to the raw layered values
+    pub fn effective_values(&self) -> toml::Table {
+        match toml::Value::try_from(&self.config) {
+            Ok(toml::Value::Table(mut values)) => {
+                shorten_floats(&mut values);
+                values
+            }
+            _ => toml::Table::new(),
+        }
+    }
+
+    /// Layer the effective value of `key` comes from
+    pub fn origin(&self, key: &str, effective: &toml::Value) -> ConfigSource {
+        match self.origins.get(key) {
+            // A value replaced while deserializing comes from the defaults
+            Some(source) if get_dotted(&self.values, key) == Some(effective) => source.clone(),
+            _ => ConfigSource::Default,
+        }
+    }
+}
+
+/// Lists the dotted keys of every non-table value in a TOML table
+pub fn flatten_keys(table: &toml::Table, prefix: &str) -> Vec<String> {
+    let mut keys = Vec::new();
+
+    for (key, value) in table {
+        let full_key = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{prefix}.{key}")
+        };
+
+        match value {
+            toml::Value::Table(inner) => keys.extend(flatten_keys(inner, &full_key)),
+            _ => keys.push(full_key),
+        }
+    }
+
+    keys
+}
+
+/// Looks up a dotted key inside a TOML table
+pub fn get_dotted<'a>(table: &'a toml::Table, key: &str) -> Option<&'a toml::Value> {
+    let (head, rest) = match key.split_once('.') {
+        Some((head, rest)) => (head, Some(rest)),
+        None => (key, None),
+    };
+
+    match (table.get(head)?, rest) {
+        (value, None) => Some(value),
+        (toml::Value::Table(inner), Some(rest)) => get_dotted(inner, rest),
+        _ => None,
+    }
+}
+
+/// Removes a dotted key from a TOML table, if present
+fn remove_dotted(table: &mut toml::Table, key: &str) {
+    match key.split_once('.') {
+        Some((head, rest)) => {
+            if let Some(toml::Value::Table(inner)) = table.get_mut(head) {
+                remove_dotted(inner, rest);
+            }
+        }
+        None => {
+            table.remove(key);
+        }
+    }
+}
+
+/// Puts back the default of every value that does not deserialize on top of the defaults,
+/// so one wrong value only loses its own key and not the other keys and layers
+fn drop_invalid_values(resolved: &mut ResolvedConfig, defaults: &toml::Table) {
+    for key in flatten_keys(&resolved.values.clone(), "") {
+        let Some(value) = get_dotted(&resolved.values, &key) else {
+            continue;
+        };
+        let mut candidate = defaults.clone();
+        set_dotted(&mut candidate, &key, value.clone());
+        let Err(e) = toml::Value::Table(candidate).try_into::<Config>() else {
+            continue;
+        };
+
+        let source = resolved
+            .origins
+            .remove(&key)
+            .unwrap_or(ConfigSource::Default);
+        eprintln!("Warning: Ignoring '{key}' from {source}: {}", e.message());
+        match get_dotted(defaults, &key) {
+            Some(default) => {
+                set_dotted(&mut resolved.values, &key, default.clone());
+                resolved.origins.insert(key, ConfigSource::Default);
+            }
+            None => remove_dotted(&mut resolved.values, &key),
+        }
+    }
+}
+
+/// Merges a layer into the resolved values and records it as the origin of its keys
+fn apply_layer(resolved: &mut ResolvedConfig, layer: toml::Table, source: ConfigSource) {
+    for key in flatten_keys(&layer, "") {
+        resolved.origins.insert(key, source.clone());
+    }
+    merge_tables(&mut resolved.values, layer);
+}
+
+/// Resolves the configuration by layering, from lowest to highest priority:
+/// built-in defaults, the global file, the repo-local `.commayte.toml`,
+/// `COMMAYTE_*` environment variables and finally command-line flags.
+pub fn resolve_config(cli_overrides: &[(&str, String)]) -> ResolvedConfig {
+    let mut resolved = ResolvedConfig {
+        config: Config::default(),
+        values: toml::Table::new(),
+        origins: BTreeMap::new(),
+    };
+
+    let defaults = match toml::Value::try_from(Config::default()) {
+        Ok(toml::Value::Table(defaults)) => defaults,
+        _ => toml::Table::new(),
+    };
+    apply_layer(&mut resolved, defaults.clone(), ConfigSource::Default);
+
+    let global_path = get_config_path();
+    if let Some(global) = read_config_layer(&global_path) {
+        apply_layer(&mut resolved, global, ConfigSource::Global(global_path));
+    }
+
+    if let Some(repo_path) = find_repo_config_path() {
+        if let Some(repo) = read_config_layer(&repo_path) {
+            apply_layer(&mut resolved, repo, ConfigSource::Repo(repo_path));
+        }
+    }
+
+    for (key, _) in KEYS {
+        let var = env_var_name(key);
+        if let Ok(raw) = std::env::var(&var) {
+            match parse_value(key, &raw) {
+                Ok(value) => {
+                    set_dotted(&mut resolved.values, key, value);
+                    resolved
+                        .origins
+                        .insert(key.to_string(), ConfigSource::Env(var));
+                }
+                Err(e) => eprintln!("Warning: Ignoring {var}: {e}"),
+            }
+        }
+    }
+
+    for (key, raw) in cli_overrides {
+        match parse_value(key, raw) {
+            Ok(value) => {
+                set_dotted(&mut resolved.values, key, value);
+                resolved.origins.insert(key.to_string(), ConfigSource::Cli);
+            }
+            Err(e) => eprintln!("Warning: Ignoring command-line value for '{key}': {e}"),
+        }
+    }
+
+    match toml::Value::Table(resolved.values.clone()).try_into() {
+        Ok(config) => resolved.config = config,
+        Err(_) => {
+            drop_invalid_values(&mut resolved, &defaults);
+            match toml::Value::Table(resolved.values.clone()).try_into() {
+                Ok(config) => resolved.config = config,
+                Err(e) => {
+                    eprintln!("Warning: Invalid configuration: {e}");
+                    eprintln!("Using default configuration");
+                }
+            }
+        }
+    }
+
+    resolved
+}
+
+/// Loads the fully layered configuration
+pub fn load_config(cli_overrides: &[(&str, String)]) -> Config {
+    resolve_config(cli_overrides).config
+}
+
+/// A problem found while validating a configuration file
+pub struct ConfigIssue {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+/// Converts a byte offset into a 1-based line and column
+fn line_col(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset.min(content.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map(|i| i + 1).unwrap_or(0) + 1;
+    (line, column)
+}
+
+/// Human-readable name of a TOML value's type
+fn type_name(value: &toml_edit::Value) -> &'static str {
+    match value {
+        toml_edit::Value::String(_) => "string",
+        toml_edit::Value::Integer(_) => "integer",
+        toml_edit::Value::Float(_) => "float",
+        toml_edit::Value::Boolean(_) => "boolean",
+        toml_edit::Value::Datetime(_) => "datetime",
+        toml_edit::Value::Array(_) => "array",
+        toml_edit::Value::InlineTable(_) => "table",
+    }
+}
+
+/// Checks a single value against the kind expected for its key
+fn check_value_kind(key: &str, kind: ValueKind, value: &toml_edit::Value) -> Option<String> {
+    let matches = match kind {
+        ValueKind::String => value.is_str(),
+        ValueKind::Integer => value.is_integer(),
+        ValueKind::Float => value.is_float() || value.is_integer(),
+        ValueKind::Boolean => value.is_bool(),
+        ValueKind::StringList => value
+            .as_array()
+            .is_some_and(|items| items.iter().all(|item| item.is_str())),
+        ValueKind::TypeList => value.as_array().is_some_and(|items| {
+            items.iter().all(|item| {
+                item.as_inline_table()
+                    .is_some_and(|table| is_commit_type_table(table))
+            })
+        }),
+        ValueKind::Enum(allowed) => {
+            if let Some(text) = value.as_str() {
+                if !allowed.contains(&text) {
+                    return Some(format!(
+                        "'{key}' must be one of {}, got \"{text}\"",
+                        allowed.join(", ")
+                    ));
+                }
+                true
+            } else {
+                false
+            }
+        }
+    };
+
+    if matches {
+        None
+    } else {
+        let expected = match kind {
+            ValueKind::String | ValueKind::Enum(_) => "string",
+            ValueKind::Integer => "integer",
+            ValueKind::Float => "number",
+            ValueKind::Boolean => "boolean",
+            ValueKind::StringList => "list of strings",
+            ValueKind::TypeList => "list of { name, description } tables",
+        };
+        Some(format!(
+            "'{key}' expects a {expected}, found {}",
+            type_name(value)
+        ))
+    }
+}
+
+/// Checks that a table describes a commit type: a string `name` and an optional string `description`
+fn is_commit_type_table(table: &dyn toml_edit::TableLike) -> bool {
+    let has_name = table.get("name").and_then(|name| name.as_str()).is_some();
+    let valid_description = match table.get("description") {
+        Some(description) => description.as_str().is_some(),
+        None => true,
+    };
+    let known_keys = table
+        .iter()
+        .all(|(key, _)| key == "name" || key == "description");
+
+    has_name && valid_description && known_keys
+}
+
+/// Recursively checks every key of a parsed document against [`KEYS`]
+fn validate_table(
+    content: &str,
+    table: &dyn toml_edit::TableLike,
+    prefix: &str,
+    issues: &mut Vec<ConfigIssue>,
+) {
+    for (key, item) in table.iter() {
+        let full_key = if prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{prefix}.{key}")
+        };
+
+        let span = table
+            .get_key_value(key)
+            .and_then(|(k, _)| k.span())
+            .or_else(|| item.span());
+        let (line, column) = span
+            .map(|span| line_col(content, span.start))
+            .unwrap_or((0, 0));
+
+        if let Some(inner) = item.as_table_like() {
+            let section_prefix = format!("{full_key}.");
+            if KEYS.iter().any(|(k, _)| k.starts_with(&section_prefix)) {
+                validate_table(content, inner, &full_key, issues);
+            } else {
+                issues.push(ConfigIssue {
+                    line,
+                    column,
+                    message: format!("unknown section '{full_key}'"),
+                });
+            }
+            continue;
+        }
+
+        // `[[commit.types]]` sections are arrays of tables rather than values
+        if let Some(array) = item.as_array_of_tables() {
+            if key_kind(&full_key) == Some(ValueKind::TypeList) {
+                if !array.iter().all(|table| is_commit_type_table(table)) {
+                    issues.push(ConfigIssue {
+                        line,
+                        column,
+                        message: format!(
+                            "'{full_key}' entries need a string 'name' and an optional string 'description'"
+                        ),
+                    });
+                }
+            } else {
+                issues.push(ConfigIssue {
+                    line,
+                    column,
+                    message: format!("unknown key '{full_key}'"),
+                });
+            }
+            continue;
+        }
+
+        match (key_kind(&full_key), item.as_value()) {
+            (None, _) => issues.push(ConfigIssue {
+                line,
+                column,
+                message: format!("unknown key '{full_key}'"),
+            }),
+            (Some(kind), Some(value)) => {
+                if let Some(message) = check_value_kind(&full_key, kind, value) {
+                    issues.push(ConfigIssue {
+                        line,
+                        column,
+                        message,
+                    });
+                }
+            }
+            (Some(_), None) => issues.push(ConfigIssue {
+                line,
+                column,
+                message: format!("'{full_key}' has an unsupported value"),
+            }),
+        }
+    }
+}
+
+/// Validates a configuration file, reporting syntax errors, unknown keys and wrong types
+pub fn validate_config_content(content: &str) -> Vec<ConfigIssue> {
+    let document = match toml_edit::ImDocument::parse(content) {
+        Ok(document) => document,
+        Err(e) => {
+            let (line, column) = e
+                .span()
+                .map(|span| line_col(content, span.start))
+                .unwrap_or((0, 0));
+            return vec![ConfigIssue {
+                line,
+                column,
+                message: e.message().trim().replace('\n', "; "),
+            }];
+        }
+    };
+
+    let mut issues = Vec::new();
+    validate_table(content, document.as_table(), "", &mut issues);
+    issues
+}
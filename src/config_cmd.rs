@@ -0,0 +1,233 @@
+use anyhow::{anyhow, Result};
+use clap::Subcommand;
+use colored::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::config;
+
+#[derive(Subcommand)]
+pub enum ConfigAction {
+    /// Print the resolved value of a key (e.g. `ollama.host`)
+    Get { key: String },
+    /// Write a key to the global config file (or the repo file with --local)
+    Set {
+        key: String,
+        value: String,
+        /// Write to the repo-local .commayte.toml instead of the global file
+        #[arg(long)]
+        local: bool,
+    },
+    /// List every resolved setting
+    List {
+        /// Show which layer each value comes from
+        #[arg(long)]
+        show_origin: bool,
+    },
+    /// Open the config file in $VISUAL / $EDITOR
+    Edit {
+        /// Edit the repo-local .commayte.toml instead of the global file
+        #[arg(long)]
+        local: bool,
+    },
+    /// Check config files for syntax errors, unknown keys and wrong types
+    Validate,
+}
+
+pub fn run(action: &ConfigAction, cli_overrides: &[(&str, String)]) -> Result<()> {
+    match action {
+        ConfigAction::Get { key } => get(key, cli_overrides),
+        ConfigAction::Set { key, value, local } => set(key, value, *local),
+        ConfigAction::List { show_origin } => list(*show_origin, cli_overrides),
+        ConfigAction::Edit { local } => edit(*local),
+        ConfigAction::Validate => validate(),
+    }
+}
+
+/// Formats a value the way it would be passed on the command line
+fn display_value(value: &toml::Value) -> String {
+    match value {
+        toml::Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Picks the file `set` and `edit` operate on
+fn target_path(local: bool) -> Result<PathBuf> {
+    if local {
+        config::repo_config_path().ok_or_else(|| anyhow!("Not inside a git repository"))
+    } else {
+        Ok(config::get_config_path())
+    }
+}
+
+fn get(key: &str, cli_overrides: &[(&str, String)]) -> Result<()> {
+    if config::key_kind(key).is_none() {
+        return Err(anyhow!("Unknown configuration key '{key}'"));
+    }
+
+    let values = config::resolve_config(cli_overrides).effective_values();
+    match config::get_dotted(&values, key) {
+        Some(value) => {
+            println!("{}", display_value(value));
+            Ok(())
+        }
+        // Unset optional keys behave like `git config --get`: no output, non-zero exit
+        None => std::process::exit(1),
+    }
+}
+
+/// Converts a TOML value into its `toml_edit` counterpart
+fn to_edit_value(value: toml::Value) -> toml_edit::Value {
+    match value {
+        toml::Value::String(text) => text.into(),
+        toml::Value::Integer(number) => number.into(),
+        toml::Value::Float(number) => number.into(),
+        toml::Value::Boolean(flag) => flag.into(),
+        toml::Value::Array(items) => items
+            .into_iter()
+            .map(to_edit_value)
+            .collect::<toml_edit::Array>()
+            .into(),
+        other => other.to_string().into(),
+    }
+}
+
+fn set(key: &str, raw: &str, local: bool) -> Result<()> {
+    let value = config::parse_value(key, raw)?;
+    let path = target_path(local)?;
+
+    // Edit the document in place so comments and formatting survive
+    let content = fs::read_to_string(&path).unwrap_or_default();
+    let mut document: toml_edit::DocumentMut = content.parse().map_err(|e| {
+        anyhow!("Cannot update {path:?}, it is not valid TOML: {e}\nRun 'commayte config validate' for details")
+    })?;
+
+    let mut parts: Vec<&str> = key.split('.').collect();
+    let last = parts.pop().unwrap();
+    let mut table = document.as_table_mut();
+    for part in parts {
+        table = table
+            .entry(part)
+            .or_insert_with(toml_edit::table)
+            .as_table_mut()
+            .ok_or_else(|| anyhow!("'{part}' is not a table in {path:?}"))?;
+    }
+    table[last] = toml_edit::value(to_edit_value(value));
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, document.to_string())?;
+
+    println!("✅ Set {} in {}", key.bold(), path.display());
+    Ok(())
+}
+
+fn list(show_origin: bool, cli_overrides: &[(&str, String)]) -> Result<()> {
+    let resolved = config::resolve_config(cli_overrides);
+    let values = resolved.effective_values();
+
+    for key in config::flatten_keys(&values, "") {
+        let Some(value) = config::get_dotted(&values, &key) else {
+            continue;
+        };
+
+        if show_origin {
+            let origin = resolved.origin(&key, value).to_string();
+            println!("{:<40} {} = {}", origin.dimmed(), key, value);
+        } else {
+            println!("{key} = {value}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Launches the user's editor on a file and waits for it to exit
+fn open_in_editor(path: &Path) -> Result<()> {
+    let default_editor = if cfg!(windows) { "notepad" } else { "vi" };
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| default_editor.to_string());
+
+    // Editors are often configured with arguments, e.g. "code --wait"
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or(default_editor);
+
+    let status = Command::new(program).args(parts).arg(path).status()?;
+    if !status.success() {
+        return Err(anyhow!("Editor exited with status {status}"));
+    }
+
+    Ok(())
+}
+
+fn edit(local: bool) -> Result<()> {
+    let path = target_path(local)?;
+
+    if !path.exists() {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let model = config::Config::default().model;
+        fs::write(&path, format!("model = \"{model}\"\n"))?;
+    }
+
+    open_in_editor(&path)?;
+
+    if !validate_file(&path)? {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Prints the issues found in a file and returns whether it is valid
+fn validate_file(path: &Path) -> Result<bool> {
+    let content = fs::read_to_string(path)?;
+    let issues = config::validate_config_content(&content);
+
+    if issues.is_empty() {
+        println!("✅ {}", path.display());
+        return Ok(true);
+    }
+
+    println!("❌ {}", path.display());
+    for issue in issues {
+        println!(
+            "   {}:{}:{}: {}",
+            path.display(),
+            issue.line,
+            issue.column,
+            issue.message.red()
+        );
+    }
+    Ok(false)
+}
+
+fn validate() -> Result<()> {
+    let mut paths = vec![config::get_config_path()];
+    if let Some(repo_path) = config::find_repo_config_path() {
+        paths.push(repo_path);
+    }
+
+    let mut all_valid = true;
+    let mut checked_any = false;
+    for path in paths.iter().filter(|path| path.exists()) {
+        checked_any = true;
+        all_valid &= validate_file(path)?;
+    }
+
+    if !checked_any {
+        println!(
+            "{}",
+            "⚠️  No configuration file found, using defaults.".yellow()
+        );
+    }
+
+    if !all_valid {
+        std::process::exit(1);
+    }
+    Ok(())
+}
@@ -0,0 +1,476 @@
+use std::fmt;
+
+/// A trailer at the end of a commit message, e.g. `Refs: #12` or `BREAKING CHANGE: ...`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Footer {
+    pub token: String,
+    pub value: String,
+    /// Written as `Token #value` rather than `Token: value`
+    pub uses_hash: bool,
+}
+
+/// A commit message following the Conventional Commits specification
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConventionalCommit {
+    pub commit_type: String,
+    pub scope: Option<String>,
+    pub breaking: bool,
+    pub description: String,
+    pub body: Option<String>,
+    pub footers: Vec<Footer>,
+    /// Whether the header itself carried the `!` marker
+    header_bang: bool,
+}
+
+/// Why a message is not a valid conventional commit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The message is empty or only whitespace
+    Empty,
+    /// The header has no type before the scope or colon
+    MissingType,
+    /// The type contains characters other than letters, digits and `-`
+    InvalidType(String),
+    /// The type is well-formed but not in the list of allowed types
+    UnknownType(String),
+    /// The scope is not in the list of allowed scopes
+    UnknownScope(String),
+    /// A `(` opens a scope that is never closed
+    UnclosedScope,
+    /// The scope parentheses are empty
+    EmptyScope,
+    /// The header has no `:` after the type and scope
+    MissingColon,
+    /// The colon is not followed by a space
+    MissingSpaceAfterColon,
+    /// Nothing follows `type(scope): `
+    EmptyDescription,
+    /// The body starts right after the header without a blank line
+    MissingBlankLine,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "message is empty"),
+            ParseError::MissingType => write!(f, "missing commit type before ':'"),
+            ParseError::InvalidType(commit_type) => {
+                write!(f, "'{commit_type}' is not a valid commit type")
+            }
+            ParseError::UnknownType(commit_type) => {
+                write!(f, "'{commit_type}' is not an allowed commit type")
+            }
+            ParseError::UnknownScope(scope) => write!(f, "'{scope}' is not an allowed scope"),
+            ParseError::UnclosedScope => write!(f, "scope is missing its closing ')'"),
+            ParseError::EmptyScope => write!(f, "scope is empty"),
+            ParseError::MissingColon => write!(f, "missing ':' after the type"),
+            ParseError::MissingSpaceAfterColon => write!(f, "missing space after ':'"),
+            ParseError::EmptyDescription => write!(f, "description is empty"),
+            ParseError::MissingBlankLine => {
+                write!(f, "body must be separated from the header by a blank line")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Returns true for trailer lines such as `BREAKING CHANGE: ...`, `Refs: #12` or `Fixes #3`
+pub fn is_footer_line(line: &str) -> bool {
+    parse_footer(line).is_some()
+}
+
+/// Splits a trailer line into its token and value
+fn parse_footer(line: &str) -> Option<Footer> {
+    for token in ["BREAKING CHANGE", "BREAKING-CHANGE"] {
+        if let Some(value) = line.strip_prefix(token).and_then(|r| r.strip_prefix(": ")) {
+            return Some(Footer {
+                token: token.to_string(),
+                value: value.trim().to_string(),
+                uses_hash: false,
+            });
+        }
+    }
+
+    let token_end = line
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+        .unwrap_or(line.len());
+    let (token, rest) = line.split_at(token_end);
+    if token.is_empty() || !token.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return None;
+    }
+
+    let (value, uses_hash) = if let Some(value) = rest.strip_prefix(": ") {
+        (value, false)
+    } else if let Some(value) = rest.strip_prefix(' ').filter(|v| v.starts_with('#')) {
+        (value, true)
+    } else {
+        return None;
+    };
+
+    Some(Footer {
+        token: token.to_string(),
+        value: value.trim().to_string(),
+        uses_hash,
+    })
+}
+
+impl Footer {
+    /// Returns true for `BREAKING CHANGE` and `BREAKING-CHANGE` trailers
+    pub fn is_breaking(&self) -> bool {
+        self.token == "BREAKING CHANGE" || self.token == "BREAKING-CHANGE"
+    }
+}
+
+impl ConventionalCommit {
+    /// Parses a full commit message (header, optional body and footers)
+    pub fn parse(message: &str) -> Result<Self, ParseError> {
+        let message = message.trim();
+        if message.is_empty() {
+            return Err(ParseError::Empty);
+        }
+
+        let mut lines = message.lines();
+        let header = lines.next().unwrap_or("").trim();
+        let mut commit = Self::parse_header(header)?;
+
+        let rest: Vec<&str> = lines.collect();
+        if rest.first().is_some_and(|line| !line.trim().is_empty()) {
+            return Err(ParseError::MissingBlankLine);
+        }
+
+        let rest = rest.join("\n");
+        let mut paragraphs: Vec<&str> = rest
+            .split("\n\n")
+            .map(|p| p.trim_matches('\n'))
+            .filter(|p| !p.trim().is_empty())
+            .collect();
+
+        // Footers live in the last paragraph, starting with a trailer line
+        if let Some(last) = paragraphs.last() {
+            if last.lines().next().is_some_and(is_footer_line) {
+                commit.footers = parse_footers(last);
+                paragraphs.pop();
+            }
+        }
+
+        if !paragraphs.is_empty() {
+            commit.body = Some(paragraphs.join("\n\n"));
+        }
+        commit.breaking |= commit.footers.iter().any(Footer::is_breaking);
+
+        Ok(commit)
+    }
+
+    /// Parses only the `type(scope)!: description` header line
+    pub fn parse_header(header: &str) -> Result<Self, ParseError> {
+        let header = header.trim();
+        if header.is_empty() {
+            return Err(ParseError::Empty);
+        }
+
+        let type_end = header
+            .find(['(', '!', ':'])
+            .ok_or(ParseError::MissingColon)?;
+        let commit_type = &header[..type_end];
+        if commit_type.is_empty() {
+            return Err(ParseError::MissingType);
+        }
+        if !commit_type
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-')
+        {
+            return Err(ParseError::InvalidType(commit_type.to_string()));
+        }
+
+        let mut rest = &header[type_end..];
+
+        let mut scope = None;
+        if let Some(after_paren) = rest.strip_prefix('(') {
+            let close = after_paren.find(')').ok_or(ParseError::UnclosedScope)?;
+            let scope_text = after_paren[..close].trim();
+            if scope_text.is_empty() {
+                return Err(ParseError::EmptyScope);
+            }
+            scope = Some(scope_text.to_string());
+            rest = &after_paren[close + 1..];
+        }
+
+        let breaking = rest.starts_with('!');
+        if breaking {
+            rest = &rest[1..];
+        }
+
+        let rest = rest.strip_prefix(':').ok_or(ParseError::MissingColon)?;
+        if rest.trim().is_empty() {
+            return Err(ParseError::EmptyDescription);
+        }
+        let description = rest
+            .strip_prefix(' ')
+            .ok_or(ParseError::MissingSpaceAfterColon)?
+            .trim();
+
+        Ok(Self {
+            commit_type: commit_type.to_string(),
+            scope,
+            breaking,
+            description: description.to_string(),
+            body: None,
+            footers: Vec::new(),
+            header_bang: breaking,
+        })
+    }
+
+    /// Checks the type against a list of allowed types
+    pub fn check_type(&self, allowed: &[&str]) -> Result<(), ParseError> {
+        if allowed.contains(&self.commit_type.as_str()) {
+            Ok(())
+        } else {
+            Err(ParseError::UnknownType(self.commit_type.clone()))
+        }
+    }
+
+    /// Checks every scope (several may be separated by `,` or `/`) against the allowed ones.
+    /// An empty list accepts any scope.
+    pub fn check_scope(&self, allowed: &[&str]) -> Result<(), ParseError> {
+        let Some(scope) = &self.scope else {
+            return Ok(());
+        };
+        if allowed.is_empty() {
+            return Ok(());
+        }
+
+        match scope
+            .split([',', '/'])
+            .map(str::trim)
+            .find(|part| !allowed.contains(part))
+        {
+            Some(unknown) => Err(ParseError::UnknownScope(unknown.to_string())),
+            None => Ok(()),
+        }
+    }
+
+    /// Renders the `type(scope)!: description` header line
+    pub fn header(&self) -> String {
+        let scope = self
+            .scope
+            .as_ref()
+            .map(|scope| format!("({scope})"))
+            .unwrap_or_default();
+        let bang = if self.header_bang { "!" } else { "" };
+        format!("{}{scope}{bang}: {}", self.commit_type, self.description)
+    }
+}
+
+/// Parses a footer paragraph, appending continuation lines to the previous trailer
+fn parse_footers(paragraph: &str) -> Vec<Footer> {
+    let mut footers: Vec<Footer> = Vec::new();
+
+    for line in paragraph.lines() {
+        match parse_footer(line) {
+            Some(footer) => footers.push(footer),
+            None => {
+                if let Some(previous) = footers.last_mut() {
+                    previous.value.push('\n');
+                    previous.value.push_str(line.trim_end());
+                }
+            }
+        }
+    }
+
+    footers
+}
+
+impl fmt::Display for ConventionalCommit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.header())?;
+
+        if let Some(body) = &self.body {
+            write!(f, "\n\n{body}")?;
+        }
+
+        if !self.footers.is_empty() {
+            writeln!(f)?;
+            for footer in &self.footers {
+                if footer.uses_hash {
+                    write!(f, "\n{} {}", footer.token, footer.value)?;
+                } else {
+                    write!(f, "\n{}: {}", footer.token, footer.value)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai;
+    use crate::config::CommitConfig;
+
+    fn parse_error(message: &str) -> ParseError {
+        ConventionalCommit::parse(message).unwrap_err()
+    }
+
+    #[test]
+    fn parses_type_and_description() {
+        let commit = ConventionalCommit::parse("fix: handle empty diffs").unwrap();
+
+        assert_eq!(commit.commit_type, "fix");
+        assert_eq!(commit.scope, None);
+        assert!(!commit.breaking);
+        assert_eq!(commit.description, "handle empty diffs");
+        assert_eq!(commit.body, None);
+        assert!(commit.footers.is_empty());
+    }
+
+    #[test]
+    fn parses_scope() {
+        let commit = ConventionalCommit::parse("feat(git): add a libgit2 backend").unwrap();
+
+        assert_eq!(commit.scope.as_deref(), Some("git"));
+        assert_eq!(commit.header(), "feat(git): add a libgit2 backend");
+    }
+
+    #[test]
+    fn bang_marks_a_breaking_change() {
+        let commit = ConventionalCommit::parse("refactor(config)!: rename keys").unwrap();
+
+        assert!(commit.breaking);
+        assert_eq!(commit.scope.as_deref(), Some("config"));
+        assert_eq!(commit.header(), "refactor(config)!: rename keys");
+        assert!(
+            ConventionalCommit::parse("feat!: drop v1")
+                .unwrap()
+                .breaking
+        );
+    }
+
+    #[test]
+    fn breaking_change_footer_marks_a_breaking_change() {
+        for token in ["BREAKING CHANGE", "BREAKING-CHANGE"] {
+            let message = format!("feat: new config format\n\n{token}: the old file is ignored");
+            let commit = ConventionalCommit::parse(&message).unwrap();
+
+            assert!(commit.breaking);
+            assert_eq!(commit.footers.len(), 1);
+            assert!(commit.footers[0].is_breaking());
+            assert_eq!(commit.footers[0].value, "the old file is ignored");
+            // The header keeps its original form
+            assert_eq!(commit.header(), "feat: new config format");
+        }
+    }
+
+    #[test]
+    fn separates_body_and_footers() {
+        let message = "fix(lint): count characters\n\n\
+            Headers were measured in bytes.\n\n\
+            Emoji made them look too long.\n\n\
+            Fixes #42\n\
+            Refs: #40\n  and #41";
+        let commit = ConventionalCommit::parse(message).unwrap();
+
+        assert_eq!(
+            commit.body.as_deref(),
+            Some("Headers were measured in bytes.\n\nEmoji made them look too long.")
+        );
+        assert_eq!(
+            commit.footers,
+            [
+                Footer {
+                    token: "Fixes".to_string(),
+                    value: "#42".to_string(),
+                    uses_hash: true,
+                },
+                Footer {
+                    token: "Refs".to_string(),
+                    value: "#40\n  and #41".to_string(),
+                    uses_hash: false,
+                },
+            ]
+        );
+        assert!(!commit.breaking);
+        assert_eq!(commit.to_string(), message);
+    }
+
+    #[test]
+    fn a_body_paragraph_is_not_a_footer() {
+        let commit =
+            ConventionalCommit::parse("docs: explain layers\n\nSee the README for details.")
+                .unwrap();
+
+        assert!(commit.footers.is_empty());
+        assert_eq!(commit.body.as_deref(), Some("See the README for details."));
+        assert!(is_footer_line("Closes #7"));
+        assert!(!is_footer_line("See the README: it explains"));
+    }
+
+    #[test]
+    fn emoji_prefix_is_split_before_parsing() {
+        let (emoji, rest) = ai::split_leading_emoji("✨ feat(ui): add a spinner");
+        assert_eq!(emoji, Some("✨"));
+        assert_eq!(rest, "feat(ui): add a spinner");
+
+        // The parser itself only accepts a bare header
+        assert_eq!(
+            parse_error("✨ feat(ui): add a spinner"),
+            ParseError::InvalidType("✨ feat".to_string())
+        );
+        let commit =
+            ai::validate_commit_message("🐛 fix: stop at EOF", &CommitConfig::default()).unwrap();
+        assert_eq!(commit.header(), "fix: stop at EOF");
+    }
+
+    #[test]
+    fn reports_each_malformed_header() {
+        assert_eq!(parse_error(""), ParseError::Empty);
+        assert_eq!(parse_error(" \n "), ParseError::Empty);
+        assert_eq!(parse_error(": no type"), ParseError::MissingType);
+        assert_eq!(
+            parse_error("new feature: x"),
+            ParseError::InvalidType("new feature".to_string())
+        );
+        assert_eq!(parse_error("feat(api: x"), ParseError::UnclosedScope);
+        assert_eq!(parse_error("feat( ): x"), ParseError::EmptyScope);
+        assert_eq!(parse_error("add a feature"), ParseError::MissingColon);
+        assert_eq!(parse_error("feat(api) x"), ParseError::MissingColon);
+        assert_eq!(parse_error("feat:x"), ParseError::MissingSpaceAfterColon);
+        assert_eq!(parse_error("feat(api)!:   "), ParseError::EmptyDescription);
+        assert_eq!(
+            parse_error("feat: x\nbody right below"),
+            ParseError::MissingBlankLine
+        );
+    }
+
+    #[test]
+    fn checks_type_and_scope_against_allowed_lists() {
+        let commit = ConventionalCommit::parse("wip(api,db): x").unwrap();
+
+        assert_eq!(
+            commit.check_type(&["feat", "fix"]),
+            Err(ParseError::UnknownType("wip".to_string()))
+        );
+        assert!(commit.check_type(&["wip"]).is_ok());
+        assert_eq!(
+            commit.check_scope(&["api"]),
+            Err(ParseError::UnknownScope("db".to_string()))
+        );
+        assert!(commit.check_scope(&["api", "db"]).is_ok());
+        // No configured scopes accepts any
+        assert!(commit.check_scope(&[]).is_ok());
+    }
+
+    #[test]
+    fn describes_errors() {
+        assert_eq!(
+            ParseError::UnknownType("wip".to_string()).to_string(),
+            "'wip' is not an allowed commit type"
+        );
+        assert_eq!(
+            ParseError::MissingSpaceAfterColon.to_string(),
+            "missing space after ':'"
+        );
+    }
+}
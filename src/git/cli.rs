@@ -0,0 +1,301 @@
+use anyhow::{anyhow, Result};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use super::{ChangeKind, CommitInfo, GitBackend, WorktreeChange};
+
+/// Separates commits in the `git log` output parsed by `parse_log`
+const RECORD_SEPARATOR: char = '\x1e';
+
+/// Id, parent ids and message of each commit, read by `parse_log`
+const LOG_FORMAT: &str = "--format=%H%x00%P%x00%ct%x00%B%x1e";
+
+/// Message cleanup for commits, the one the libgit2 backend applies too: lines starting
+/// with `#` are kept. Set explicitly so a `commit.cleanup` setting does not change it.
+pub(super) const COMMIT_CLEANUP: &str = "--cleanup=whitespace";
+
+/// Id of the empty tree, the "parent" of a root commit
+const EMPTY_TREE: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+
+/// Backend running the `git` executable, so hooks, signing and credential helpers apply
+pub struct CliBackend;
+
+/// Runs git with `args`, optionally feeding `input` on stdin, and returns its stdout.
+/// A non-zero exit becomes an error carrying git's own message.
+pub fn run_git(args: &[&str], input: Option<&str>) -> Result<String> {
+    run_git_with_env(args, input, &[])
+}
+
+/// Like `run_git`, with extra environment variables (e.g. `GIT_AUTHOR_DATE`)
+fn run_git_with_env(args: &[&str], input: Option<&str>, env: &[(&str, &str)]) -> Result<String> {
+    let mut child = Command::new("git")
+        .args(args)
+        .envs(env.iter().copied())
+        .stdin(if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| anyhow!("Failed to run git: {e}"))?;
+
+    if let (Some(mut stdin), Some(input)) = (child.stdin.take(), input) {
+        stdin.write_all(input.as_bytes())?;
+    }
+
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        // `git commit` explains "nothing to commit" on stdout
+        let details = if stderr.trim().is_empty() {
+            stdout.trim()
+        } else {
+            stderr.trim()
+        };
+        return Err(anyhow!(
+            "git {} failed ({}): {details}",
+            args.first().unwrap_or(&""),
+            output.status
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+impl GitBackend for CliBackend {
+    fn staged_diff(&self) -> Result<String> {
+        run_git(&["diff", "--cached"], None)
+    }
+
+    fn amend_diff(&self) -> Result<String> {
+        if run_git(&["rev-parse", "--verify", "--quiet", "HEAD"], None).is_err() {
+            return Err(anyhow!("Nothing to amend, the repository has no commits"));
+        }
+        let base = if run_git(&["rev-parse", "--verify", "--quiet", "HEAD~1"], None).is_ok() {
+            "HEAD~1"
+        } else {
+            EMPTY_TREE
+        };
+        run_git(&["diff", "--cached", base], None)
+    }
+
+    fn commit(&self, message: &str) -> Result<()> {
+        // The message goes through stdin so multi-paragraph bodies and footers are kept intact
+        run_git(&["commit", COMMIT_CLEANUP, "-F", "-"], Some(message))?;
+        Ok(())
+    }
+
+    fn amend(&self, message: &str) -> Result<()> {
+        run_git(
+            &["commit", "--amend", COMMIT_CLEANUP, "-F", "-"],
+            Some(message),
+        )?;
+        Ok(())
+    }
+
+    fn staged_patch(&self) -> Result<String> {
+        run_git(&["diff", "--cached", "--binary", "--no-renames"], None)
+    }
+
+    fn reset_index(&self) -> Result<()> {
+        if run_git(&["rev-parse", "--verify", "--quiet", "HEAD"], None).is_ok() {
+            run_git(&["read-tree", "HEAD"], None)?;
+        } else {
+            run_git(&["read-tree", "--empty"], None)?;
+        }
+        Ok(())
+    }
+
+    fn apply_to_index(&self, patch: &str) -> Result<()> {
+        run_git(&["apply", "--cached", "--binary", "-"], Some(patch))?;
+        Ok(())
+    }
+
+    fn unstaged_changes(&self) -> Result<Vec<WorktreeChange>> {
+        let status = run_git(
+            &["status", "--porcelain=v1", "-z", "--untracked-files=all"],
+            None,
+        )?;
+
+        let mut changes = Vec::new();
+        let mut entries = status.split('\0').filter(|entry| !entry.is_empty());
+        while let Some(entry) = entries.next() {
+            let (Some(index), Some(worktree), Some(path)) =
+                (entry.chars().next(), entry.chars().nth(1), entry.get(3..))
+            else {
+                continue;
+            };
+
+            // Renames and copies in the index are followed by their original path
+            if matches!(index, 'R' | 'C') {
+                entries.next();
+            }
+
+            let kind = match worktree {
+                '?' => ChangeKind::Untracked,
+                'D' => ChangeKind::Deleted,
+                ' ' => continue,
+                _ => ChangeKind::Modified,
+            };
+            changes.push(WorktreeChange {
+                path: path.to_string(),
+                kind,
+            });
+        }
+
+        Ok(changes)
+    }
+
+    fn stage(&self, paths: &[String]) -> Result<()> {
+        // Status paths are relative to the repository root, not the current directory
+        let pathspecs: Vec<String> = paths
+            .iter()
+            .map(|path| format!(":(top,literal){path}"))
+            .collect();
+        let mut args = vec!["add", "--all", "--"];
+        args.extend(pathspecs.iter().map(String::as_str));
+        run_git(&args, None)?;
+        Ok(())
+    }
+
+    fn history(&self, limit: usize) -> Result<Vec<CommitInfo>> {
+        // A repository without commits has no history rather than an error
+        if run_git(&["rev-parse", "--verify", "--quiet", "HEAD"], None).is_err() {
+            return Ok(Vec::new());
+        }
+
+        let count = limit.to_string();
+        let log = run_git(&["log", "-n", &count, LOG_FORMAT], None)?;
+
+        Ok(parse_log(&log))
+    }
+
+    fn history_for_paths(&self, paths: &[String], limit: usize) -> Result<Vec<CommitInfo>> {
+        if paths.is_empty() || run_git(&["rev-parse", "--verify", "--quiet", "HEAD"], None).is_err()
+        {
+            return Ok(Vec::new());
+        }
+
+        let count = limit.to_string();
+        let pathspecs: Vec<String> = paths
+            .iter()
+            .map(|path| format!(":(top,literal){path}"))
+            .collect();
+        let mut args = vec!["log", "-n", &count, LOG_FORMAT, "--"];
+        args.extend(pathspecs.iter().map(String::as_str));
+        Ok(parse_log(&run_git(&args, None)?))
+    }
+
+    fn commits_in_range(&self, range: &str) -> Result<Vec<CommitInfo>> {
+        let log = run_git(&["log", "--reverse", LOG_FORMAT, range], None)?;
+        Ok(parse_log(&log))
+    }
+
+    fn previous_tag(&self, rev: &str) -> Result<Option<String>> {
+        // Starting from the parent skips a tag on `rev`; a root commit has no earlier tag
+        let parent = format!("{rev}^");
+        if run_git(&["rev-parse", "--verify", "--quiet", &parent], None).is_err() {
+            return Ok(None);
+        }
+        Ok(
+            run_git(&["describe", "--tags", "--abbrev=0", &parent], None)
+                .ok()
+                .map(|tag| tag.trim().to_string()),
+        )
+    }
+
+    fn branch_diff(&self, base: &str) -> Result<String> {
+        run_git(&["diff", &format!("{base}...HEAD")], None)
+    }
+
+    fn commit_diff(&self, id: &str) -> Result<String> {
+        run_git(&["diff-tree", "-p", "--root", "--no-commit-id", id], None)
+    }
+
+    fn is_pushed(&self, id: &str) -> Result<bool> {
+        let branches = run_git(&["branch", "--remotes", "--contains", id], None)?;
+        Ok(!branches.trim().is_empty())
+    }
+
+    fn reword(&self, rewrites: &[(String, String)]) -> Result<()> {
+        let Some((first, _)) = rewrites.first() else {
+            return Ok(());
+        };
+        let old_head = run_git(&["rev-parse", "HEAD"], None)?.trim().to_string();
+        let ids: Vec<&str> = rewrites.iter().map(|(id, _)| id.as_str()).collect();
+        super::check_linear_run(&ids, &old_head, |id| {
+            let parents = run_git(&["rev-parse", &format!("{id}^@")], None)?;
+            Ok(parents.lines().map(str::to_string).collect())
+        })?;
+
+        let mut parent = run_git(
+            &["rev-parse", "--verify", "--quiet", &format!("{first}^")],
+            None,
+        )
+        .ok()
+        .map(|id| id.trim().to_string());
+
+        for (id, message) in rewrites {
+            // `commit-tree` stores the message as is, so it gets the same cleanup as a commit
+            let message = run_git(&["stripspace"], Some(message))?;
+            let tree = run_git(&["rev-parse", &format!("{id}^{{tree}}")], None)?;
+            let author = run_git(
+                &["log", "-1", "--format=%an%x00%ae%x00%ad", "--date=raw", id],
+                None,
+            )?;
+            let mut fields = author.trim_end().splitn(3, '\0');
+            let env = [
+                ("GIT_AUTHOR_NAME", fields.next().unwrap_or("")),
+                ("GIT_AUTHOR_EMAIL", fields.next().unwrap_or("")),
+                ("GIT_AUTHOR_DATE", fields.next().unwrap_or("")),
+            ];
+
+            let mut args = vec!["commit-tree", tree.trim()];
+            if let Some(parent) = &parent {
+                args.extend(["-p", parent.as_str()]);
+            }
+            args.extend(["-F", "-"]);
+            let new_id = run_git_with_env(&args, Some(&message), &env)?;
+            parent = Some(new_id.trim().to_string());
+        }
+
+        // Fails if HEAD moved meanwhile, instead of dropping those commits
+        let new_head = parent.unwrap_or_default();
+        run_git(
+            &[
+                "update-ref",
+                "-m",
+                "commayte: reword",
+                "HEAD",
+                &new_head,
+                &old_head,
+            ],
+            None,
+        )?;
+        Ok(())
+    }
+}
+
+/// Parses `git log` output in `LOG_FORMAT`
+fn parse_log(log: &str) -> Vec<CommitInfo> {
+    log.split(RECORD_SEPARATOR)
+        .filter_map(|record| {
+            let mut fields = record.trim_start_matches('\n').splitn(4, '\0');
+            let (id, parents, time, message) = (
+                fields.next()?,
+                fields.next()?,
+                fields.next()?,
+                fields.next()?,
+            );
+            Some(CommitInfo {
+                id: id.to_string(),
+                message: message.trim().to_string(),
+                is_merge: parents.split_whitespace().count() > 1,
+                time: time.parse().unwrap_or_default(),
+            })
+        })
+        .collect()
+}
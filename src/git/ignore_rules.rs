@@ -0,0 +1,415 @@
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config;
+
+/// Repository file with extra rules, in `.gitignore` syntax
+const IGNORE_FILE: &str = ".commayteignore";
+
+/// Files left out of the diff sent to the model by default: dependency locks and manifests,
+/// build output, editor files, binaries and media. Written in `.gitignore` syntax;
+/// directories starting with `/` only match at the repository root.
+const DEFAULT_PATTERNS: &[&str] = &[
+    // Lock files and dependency managers
+    "*.lock",
+    "*.lockfile",
+    "package-lock.json",
+    "pnpm-lock.yaml",
+    "bun.lockb",
+    "go.sum",
+    "Cartfile.resolved",
+    "requirements.txt",
+    "requirements-dev.txt",
+    "pyproject.toml",
+    "setup.py",
+    "setup.cfg",
+    "package.json",
+    "go.mod",
+    "Pipfile",
+    "Gemfile",
+    "composer.json",
+    "Pods/",
+    "node_modules/",
+    "/vendor/",
+    "bower_components/",
+    "jspm_packages/",
+    // Build artifacts and compiled files
+    "/target/",
+    "/dist/",
+    "/build/",
+    "/out/",
+    "/bin/",
+    "/obj/",
+    "/Debug/",
+    "/Release/",
+    "/x64/",
+    "/x86/",
+    "*.o",
+    "*.obj",
+    "*.exe",
+    "*.dll",
+    "*.so",
+    "*.dylib",
+    "*.a",
+    "*.lib",
+    "*.class",
+    "*.jar",
+    "*.war",
+    "*.ear",
+    "*.pyc",
+    "__pycache__/",
+    "*.pyo",
+    "*.pyd",
+    "*.egg",
+    "*.egg-info/",
+    "*.whl",
+    "*.tar.gz",
+    "*.zip",
+    "*.rar",
+    "*.7z",
+    // IDE and editor files
+    ".vscode/",
+    ".idea/",
+    ".vs/",
+    "*.swp",
+    "*.swo",
+    "*~",
+    ".DS_Store",
+    "Thumbs.db",
+    "desktop.ini",
+    "*.suo",
+    "*.user",
+    "*.userosscache",
+    "*.sln.docstates",
+    "*.userprefs",
+    "*.pidb",
+    "*.booproj",
+    "*.svd",
+    "*.pdb",
+    "*.opendb",
+    "*.VC.db",
+    // Logs and temporary files
+    "*.log",
+    "*.tmp",
+    "*.temp",
+    "*.cache",
+    "*.bak",
+    "*.backup",
+    "*.old",
+    "*.orig",
+    "*.rej",
+    ".fuse_hidden*",
+    ".Trash-*",
+    ".nfs*",
+    // Environment and config files
+    ".env",
+    ".env.*",
+    "config.local.*",
+    "settings.local.*",
+    // AI/ML model files
+    "/models/",
+    "*.gguf",
+    "*.bin",
+    "*.safetensors",
+    "*.pt",
+    "*.pth",
+    "*.onnx",
+    "*.tflite",
+    "*.h5",
+    "*.pb",
+    "*.ckpt",
+    "*.weights",
+    "*.model",
+    // Database files
+    "*.db",
+    "*.sqlite",
+    "*.sqlite3",
+    "*.mdb",
+    "*.accdb",
+    // Git and version control
+    ".gitignore",
+    ".gitattributes",
+    ".gitmodules",
+    ".gitkeep",
+    ".git-blame*",
+    // Documentation and media
+    "*.pdf",
+    "*.doc",
+    "*.docx",
+    "*.xls",
+    "*.xlsx",
+    "*.ppt",
+    "*.pptx",
+    "*.jpg",
+    "*.jpeg",
+    "*.png",
+    "*.gif",
+    "*.bmp",
+    "*.svg",
+    "*.ico",
+    "*.mp3",
+    "*.mp4",
+    "*.avi",
+    "*.mov",
+    "*.wmv",
+    "*.flv",
+    "*.webm",
+    "*.mkv",
+    "*.tar",
+    "*.gz",
+    // OS specific
+    "._*",
+    ".Spotlight-V100",
+    ".Trashes",
+    "ehthumbs.db",
+    "$RECYCLE.BIN/",
+    "*.lnk",
+    // Test coverage and reports
+    "/coverage/",
+    "*.lcov",
+    "*.coverage",
+    "/htmlcov/",
+    ".coverage",
+    "coverage.xml",
+    "junit.xml",
+    "/test-results/",
+    "/reports/",
+    "*.report",
+    "*.out",
+    // Vendored dependencies
+    "/packages/",
+    "/lib/",
+    "/libs/",
+    "/deps/",
+    "/dependencies/",
+    "/third_party/",
+    "/third-party/",
+    "/external/",
+    "/externals/",
+];
+
+/// Labels telling `explain` where a rule came from
+const DEFAULTS_SOURCE: &str = "built-in defaults";
+const EXCLUDE_SOURCE: &str = "ignore.exclude";
+const INCLUDE_SOURCE: &str = "ignore.include";
+
+/// The rule deciding whether a path is ignored
+pub struct RuleMatch {
+    /// The pattern as written, with its `!` for re-included paths
+    pub pattern: String,
+    /// Where the pattern is defined
+    pub source: String,
+    pub ignored: bool,
+}
+
+/// Decides which changed files are left out of the prompt.
+/// Rules apply in order, the last matching one wins: built-in defaults, then
+/// `.commayteignore`, then `ignore.exclude`, then `ignore.include` (re-included paths).
+pub struct IgnoreRules {
+    matcher: Gitignore,
+}
+
+impl IgnoreRules {
+    /// Builds the rules for the current repository
+    pub fn load(settings: &config::IgnoreConfig) -> Self {
+        let root = super::repo_root().unwrap_or_else(|_| PathBuf::from("."));
+        Self::for_root(&root, settings)
+    }
+
+    /// Builds the rules for the repository at `root`
+    fn for_root(root: &Path, settings: &config::IgnoreConfig) -> Self {
+        let mut builder = GitignoreBuilder::new(root);
+
+        let mut add = |source: &str, line: &str| {
+            if let Err(e) = builder.add_line(Some(PathBuf::from(source)), line) {
+                eprintln!("Warning: Ignoring pattern '{line}' from {source}: {e}");
+            }
+        };
+
+        for pattern in DEFAULT_PATTERNS {
+            add(DEFAULTS_SOURCE, pattern);
+        }
+        if let Ok(content) = fs::read_to_string(root.join(IGNORE_FILE)) {
+            for (number, line) in content.lines().enumerate() {
+                add(&format!("{IGNORE_FILE}:{}", number + 1), line);
+            }
+        }
+        for pattern in &settings.exclude {
+            add(EXCLUDE_SOURCE, pattern);
+        }
+        for pattern in &settings.include {
+            // An include is a negated exclude, accept it written either way
+            add(
+                INCLUDE_SOURCE,
+                &format!("!{}", pattern.trim_start_matches('!')),
+            );
+        }
+
+        let matcher = builder.build().unwrap_or_else(|e| {
+            eprintln!("Warning: Invalid ignore rules, using none: {e}");
+            Gitignore::empty()
+        });
+        Self { matcher }
+    }
+
+    /// Returns the rule deciding the fate of `path` (relative to the repository root),
+    /// or `None` when no rule matches and the file is kept
+    pub fn explain(&self, path: &str, is_dir: bool) -> Option<RuleMatch> {
+        let matched = self
+            .matcher
+            .matched_path_or_any_parents(Path::new(path), is_dir);
+        let glob = matched.inner()?;
+        Some(RuleMatch {
+            pattern: glob.original().to_string(),
+            source: glob
+                .from()
+                .map(|source| source.display().to_string())
+                .unwrap_or_default(),
+            ignored: matched.is_ignore(),
+        })
+    }
+
+    /// Returns true for files left out of the diff sent to the model
+    pub fn is_ignored(&self, path: &str) -> bool {
+        self.explain(path, false).is_some_and(|rule| rule.ignored)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Rules for a repository of its own, with `ignore_file` as its `.commayteignore`
+    fn rules(
+        name: &str,
+        ignore_file: Option<&str>,
+        exclude: &[&str],
+        include: &[&str],
+    ) -> IgnoreRules {
+        let root =
+            std::env::temp_dir().join(format!("commayte-ignore-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        if let Some(content) = ignore_file {
+            fs::write(root.join(IGNORE_FILE), content).unwrap();
+        }
+
+        let settings = config::IgnoreConfig {
+            exclude: exclude.iter().map(|pattern| pattern.to_string()).collect(),
+            include: include.iter().map(|pattern| pattern.to_string()).collect(),
+        };
+        let rules = IgnoreRules::for_root(&root, &settings);
+        fs::remove_dir_all(&root).unwrap();
+        rules
+    }
+
+    #[test]
+    fn defaults_leave_out_locks_builds_and_vendored_code() {
+        let rules = rules("defaults", None, &[], &[]);
+
+        for path in [
+            "Cargo.lock",
+            "web/yarn.lock",
+            "package-lock.json",
+            "web/package.json",
+            "node_modules/react/index.js",
+            "target/debug/commayte",
+            "vendor/github.com/pkg/errors/errors.go",
+            "app/lib.so",
+        ] {
+            assert!(rules.is_ignored(path), "{path} should be ignored");
+        }
+        for path in ["src/main.rs", "README.md", "Cargo.toml", "src/lib/mod.rs"] {
+            assert!(!rules.is_ignored(path), "{path} should be kept");
+        }
+
+        let rule = rules.explain("Cargo.lock", false).unwrap();
+        assert_eq!(rule.pattern, "*.lock");
+        assert_eq!(rule.source, DEFAULTS_SOURCE);
+        assert!(rules.explain("src/main.rs", false).is_none());
+    }
+
+    #[test]
+    fn rooted_directories_only_match_at_the_root() {
+        let rules = rules("rooted", None, &[], &[]);
+
+        assert!(rules.is_ignored("dist/app.js"));
+        assert!(!rules.is_ignored("web/dist/app.js"));
+        assert!(rules.is_ignored("lib/vendored.js"));
+        assert!(!rules.is_ignored("src/lib/own.js"));
+    }
+
+    #[test]
+    fn directory_patterns_match_nested_files() {
+        let rules = rules("nested", Some("generated/\n"), &["/fixtures/"], &[]);
+
+        assert!(rules.is_ignored("generated/api.rs"));
+        assert!(rules.is_ignored("generated/v1/models/user.rs"));
+        assert!(rules.is_ignored("crates/client/generated/api.rs"));
+        assert!(rules.is_ignored("fixtures/a/b/c.json"));
+        assert!(!rules.is_ignored("tests/fixtures/c.json"));
+        // A file named like the directory is not a directory
+        assert!(!rules.is_ignored("src/generated"));
+        assert!(rules.is_ignored("node_modules/@babel/core/lib/index.js"));
+    }
+
+    #[test]
+    fn exclude_and_include_patterns() {
+        let rules = rules(
+            "settings",
+            None,
+            &["*.snap", "docs/api/"],
+            &["Cargo.lock", "!docs/api/index.md"],
+        );
+
+        assert!(rules.is_ignored("tests/snapshots/cli.snap"));
+        assert!(rules.is_ignored("docs/api/types.md"));
+        assert_eq!(
+            rules
+                .explain("tests/snapshots/cli.snap", false)
+                .unwrap()
+                .source,
+            EXCLUDE_SOURCE
+        );
+
+        // Includes are negations, written with or without their `!`
+        assert!(!rules.is_ignored("Cargo.lock"));
+        assert!(!rules.is_ignored("docs/api/index.md"));
+        let rule = rules.explain("Cargo.lock", false).unwrap();
+        assert_eq!(rule.pattern, "!Cargo.lock");
+        assert_eq!(rule.source, INCLUDE_SOURCE);
+        assert!(!rule.ignored);
+    }
+
+    #[test]
+    fn later_rules_win() {
+        let ignore_file = "# keep the manifest\n!package.json\n*.generated.ts\n";
+
+        // `.commayteignore` overrides the defaults
+        let rules_from_file = rules("file", Some(ignore_file), &[], &[]);
+        assert!(!rules_from_file.is_ignored("package.json"));
+        assert!(rules_from_file.is_ignored("src/api.generated.ts"));
+        assert_eq!(
+            rules_from_file
+                .explain("package.json", false)
+                .unwrap()
+                .source,
+            ".commayteignore:2"
+        );
+
+        // `ignore.exclude` overrides `.commayteignore`, `ignore.include` overrides both
+        let rules = rules(
+            "layers",
+            Some(ignore_file),
+            &["package.json"],
+            &["src/api.generated.ts"],
+        );
+        assert!(rules.is_ignored("package.json"));
+        assert_eq!(
+            rules.explain("package.json", false).unwrap().source,
+            EXCLUDE_SOURCE
+        );
+        assert!(!rules.is_ignored("src/api.generated.ts"));
+    }
+}
@@ -0,0 +1,505 @@
+mod cli;
+mod ignore_rules;
+mod native;
+mod summary;
+
+use crate::budget;
+use crate::config;
+use anyhow::{anyhow, Result};
+use std::process::Command;
+
+pub use cli::CliBackend;
+pub use ignore_rules::IgnoreRules;
+pub use native::NativeBackend;
+
+/// A commit read from the repository history
+#[derive(Debug, Clone)]
+pub struct CommitInfo {
+    /// Full hexadecimal object id
+    pub id: String,
+    /// Full commit message, subject and body
+    pub message: String,
+    /// True for commits with more than one parent
+    pub is_merge: bool,
+    /// Committer time, in seconds since the Unix epoch
+    pub time: i64,
+}
+
+/// How a file differs between the index and the working tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Modified,
+    Deleted,
+    Untracked,
+}
+
+impl ChangeKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ChangeKind::Modified => "modified",
+            ChangeKind::Deleted => "deleted",
+            ChangeKind::Untracked => "untracked",
+        }
+    }
+}
+
+/// A file with changes that are not staged yet
+#[derive(Debug, Clone)]
+pub struct WorktreeChange {
+    pub path: String,
+    pub kind: ChangeKind,
+}
+
+/// Operations Commayte needs from a repository
+pub trait GitBackend {
+    /// Unified diff of the index against HEAD, like `git diff --cached`
+    fn staged_diff(&self) -> Result<String>;
+
+    /// Diff of the index against HEAD's parent: the last commit plus anything staged since
+    fn amend_diff(&self) -> Result<String>;
+
+    /// Commits the index with `message`
+    fn commit(&self, message: &str) -> Result<()>;
+
+    /// Replaces the last commit with the index and `message`, keeping its author
+    fn amend(&self, message: &str) -> Result<()>;
+
+    /// Staged changes as a patch `apply_to_index` accepts: binary content included,
+    /// renames shown as a deletion plus an addition
+    fn staged_patch(&self) -> Result<String>;
+
+    /// Resets the index to HEAD (or empties it before the first commit), keeping the working tree
+    fn reset_index(&self) -> Result<()>;
+
+    /// Applies a patch produced by `staged_patch` to the index only
+    fn apply_to_index(&self, patch: &str) -> Result<()>;
+
+    /// Files whose working tree content differs from the index, including untracked ones
+    fn unstaged_changes(&self) -> Result<Vec<WorktreeChange>>;
+
+    /// Adds the current content of `paths` to the index, removing deleted files
+    fn stage(&self, paths: &[String]) -> Result<()>;
+
+    /// Up to `limit` commits reachable from HEAD, newest first
+    fn history(&self, limit: usize) -> Result<Vec<CommitInfo>>;
+
+    /// Up to `limit` commits reachable from HEAD that changed one of `paths`
+    /// (relative to the repository root), newest first
+    fn history_for_paths(&self, paths: &[String], limit: usize) -> Result<Vec<CommitInfo>>;
+
+    /// Commits of a revision range such as `main..HEAD`, oldest first.
+    /// A single revision means all of its history.
+    fn commits_in_range(&self, range: &str) -> Result<Vec<CommitInfo>>;
+
+    /// Most recent tag reachable from `rev`, ignoring tags on `rev` itself
+    fn previous_tag(&self, rev: &str) -> Result<Option<String>>;
+
+    /// Changes on HEAD since it forked from `base`, like `git diff base...HEAD`
+    fn branch_diff(&self, base: &str) -> Result<String>;
+
+    /// The changes introduced by one commit, against its parent (or nothing for a root commit)
+    fn commit_diff(&self, id: &str) -> Result<String>;
+
+    /// Returns true when a remote-tracking branch already contains the commit
+    fn is_pushed(&self, id: &str) -> Result<bool>;
+
+    /// Recreates a linear run of commits ending at HEAD with new messages and moves the
+    /// branch to the result. `rewrites` pairs each commit id with its message, oldest first;
+    /// trees and authors are kept. Fails without changing anything when the commits are
+    /// not such a run (see `check_linear_run`).
+    fn reword(&self, rewrites: &[(String, String)]) -> Result<()>;
+}
+
+/// Common reasons for a failed commit or push, recognised from git's output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureKind {
+    /// A pre-commit, commit-msg or server-side hook refused the change
+    HookRejected,
+    NothingStaged,
+    /// The remote branch has commits that are not in the local branch
+    NonFastForward,
+    Auth,
+    Signing,
+    /// user.name / user.email are not configured
+    Identity,
+    Unknown,
+}
+
+impl FailureKind {
+    /// Guesses the failure kind from the error text, which includes git's stderr
+    pub fn classify(output: &str) -> Self {
+        let output = output.to_lowercase();
+        let has = |needles: &[&str]| needles.iter().any(|needle| output.contains(needle));
+
+        if has(&[
+            "nothing to commit",
+            "nothing added to commit",
+            "no changes added to commit",
+        ]) {
+            FailureKind::NothingStaged
+        } else if has(&["gpg failed to sign", "failed to sign", "signing failed"]) {
+            FailureKind::Signing
+        } else if has(&["hook declined", "hook failed", "hook rejected"]) {
+            FailureKind::HookRejected
+        } else if has(&["non-fast-forward", "fetch first", "updates were rejected"]) {
+            FailureKind::NonFastForward
+        } else if has(&[
+            "authentication failed",
+            "permission denied",
+            "could not read username",
+            "could not read from remote repository",
+            "the requested url returned error: 403",
+        ]) {
+            FailureKind::Auth
+        } else if has(&[
+            "please tell me who you are",
+            "empty ident",
+            "user.name and user.email",
+        ]) {
+            FailureKind::Identity
+        } else {
+            FailureKind::Unknown
+        }
+    }
+
+    /// Like `classify`, but blames local hooks for unexplained commit failures:
+    /// git prints nothing of its own when a pre-commit or commit-msg hook exits non-zero
+    pub fn classify_commit(output: &str) -> Self {
+        match Self::classify(output) {
+            FailureKind::Unknown if has_commit_hooks() => FailureKind::HookRejected,
+            kind => kind,
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            FailureKind::HookRejected => "rejected by a git hook",
+            FailureKind::NothingStaged => "nothing is staged",
+            FailureKind::NonFastForward => "the remote branch has new commits",
+            FailureKind::Auth => "authentication with the remote failed",
+            FailureKind::Signing => "the commit could not be signed",
+            FailureKind::Identity => "no git identity configured",
+            FailureKind::Unknown => "unexpected error",
+        }
+    }
+
+    /// Suggested next step, if there is an obvious one
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            FailureKind::HookRejected => {
+                Some("Fix the issues reported above and retry, the message is kept")
+            }
+            FailureKind::NothingStaged => Some("Stage your changes with 'git add' and retry"),
+            FailureKind::NonFastForward => Some("Run 'git pull --rebase' and push again"),
+            FailureKind::Auth => Some("Check your credentials or SSH key for this remote"),
+            FailureKind::Signing => {
+                Some("Check your signing key (user.signingkey) and that gpg-agent is running")
+            }
+            FailureKind::Identity => Some(
+                "Run 'git config --global user.name \"Your Name\"' and 'git config --global user.email you@example.com'",
+            ),
+            FailureKind::Unknown => None,
+        }
+    }
+}
+
+/// Checks that `ids`, oldest first, can be rebuilt one on top of the other: none is a
+/// merge, each is the only parent of the next and the last one is `head`.
+/// `parents_of` returns the full ids of a commit's parents.
+fn check_linear_run(
+    ids: &[&str],
+    head: &str,
+    parents_of: impl Fn(&str) -> Result<Vec<String>>,
+) -> Result<()> {
+    let mut previous: Option<&str> = None;
+    for id in ids {
+        let parents = parents_of(id)?;
+        let follows = match previous {
+            Some(previous) => parents.len() == 1 && parents[0] == previous,
+            None => parents.len() <= 1,
+        };
+        if !follows {
+            return Err(anyhow!(
+                "{id} does not follow the previous commit in a linear history, nothing was changed"
+            ));
+        }
+        previous = Some(id);
+    }
+    if previous.is_some_and(|last| last != head) {
+        return Err(anyhow!(
+            "The commits to reword do not end at HEAD, nothing was changed"
+        ));
+    }
+    Ok(())
+}
+
+/// Hooks that can make a local `git commit` fail
+const COMMIT_HOOKS: [&str; 3] = ["pre-commit", "prepare-commit-msg", "commit-msg"];
+
+/// Returns true when the repository has a hook that runs during `git commit`
+fn has_commit_hooks() -> bool {
+    let Ok(hooks_dir) = get_hooks_dir() else {
+        return false;
+    };
+    COMMIT_HOOKS
+        .iter()
+        .any(|hook| hooks_dir.join(hook).is_file())
+}
+
+/// Returns true when a `git` executable can be started
+fn git_on_path() -> bool {
+    Command::new("git")
+        .arg("--version")
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+/// Opens the repository in the current directory with the configured backend
+pub fn open_backend(settings: &config::GitConfig) -> Result<Box<dyn GitBackend>> {
+    match settings.backend {
+        config::GitBackendKind::Cli => Ok(Box::new(CliBackend)),
+        config::GitBackendKind::Libgit2 => Ok(Box::new(NativeBackend::open()?)),
+        config::GitBackendKind::Auto => {
+            if git_on_path() {
+                Ok(Box::new(CliBackend))
+            } else {
+                Ok(Box::new(NativeBackend::open()?))
+            }
+        }
+    }
+}
+
+/// Most files named when the diff does not fit, the rest are only counted
+pub const MAX_LISTED_FILES: usize = 30;
+
+/// Line ending a patch that was cut
+pub const TRUNCATED_FILE_MARKER: &str = "... (file truncated)";
+
+/// Heading of the list of files whose patch did not fit
+pub const LEFT_OUT_HEADER: &str = "... (diff truncated to fit the context window)\nFiles left out:";
+
+/// Line naming a file whose patch did not fit, with its line counts
+pub fn left_out_line(file: &FileDiff) -> String {
+    let (insertions, deletions) = line_counts(&file.patch);
+    format!("- {} (+{insertions} -{deletions})", file.path)
+}
+
+/// Cuts a file's patch to at most `limit` characters, at a line boundary
+fn truncate_patch(patch: &str, limit: usize) -> String {
+    let end = patch
+        .char_indices()
+        .nth(limit)
+        .map_or(patch.len(), |(index, _)| index);
+    let end = patch[..end].rfind('\n').unwrap_or(end);
+    format!("{}\n{TRUNCATED_FILE_MARKER}", &patch[..end])
+}
+
+/// Drops ignored files from a unified diff and fits the rest in `budget` tokens.
+/// Files share the budget by importance (see `budget::allocate`): large ones are cut
+/// and those that do not fit at all are only named. Ignored files are listed at the end
+/// with a short summary instead of their patch.
+pub fn filter_diff(diff_output: &str, budget: usize, rules: &IgnoreRules) -> String {
+    let (excluded, included): (Vec<FileDiff>, Vec<FileDiff>) = split_diff_by_file(diff_output)
+        .into_iter()
+        .partition(|file| rules.is_ignored(&file.path));
+
+    let summary = (!excluded.is_empty()).then(|| summary::summarize_excluded(&excluded));
+    // The summary and the blank line before it
+    let budget = budget.saturating_sub(
+        summary
+            .as_deref()
+            .map_or(0, |summary| budget::estimate_tokens(summary) + 1),
+    );
+
+    let mut sections = Vec::new();
+    let mut left_out = Vec::new();
+    for (file, tokens) in included.iter().zip(budget::allocate(&included, budget)) {
+        let patch = file.patch.trim_end();
+        if tokens == 0 {
+            left_out.push(left_out_line(file));
+        } else if tokens >= budget::estimate_tokens(patch) {
+            sections.push(patch.to_string());
+        } else {
+            sections.push(truncate_patch(patch, budget::tokens_to_chars(tokens)));
+        }
+    }
+
+    if !left_out.is_empty() {
+        let mut listing = format!(
+            "{LEFT_OUT_HEADER}\n{}",
+            left_out[..left_out.len().min(MAX_LISTED_FILES)].join("\n")
+        );
+        if left_out.len() > MAX_LISTED_FILES {
+            listing.push_str(&format!(
+                "\n- ... and {} more files",
+                left_out.len() - MAX_LISTED_FILES
+            ));
+        }
+        sections.push(listing);
+    }
+    sections.extend(summary);
+    sections.join("\n\n")
+}
+
+/// One file's section of a unified diff, starting at its `diff --git` line
+#[derive(Debug, Clone)]
+pub struct FileDiff {
+    pub path: String,
+    pub patch: String,
+}
+
+/// Splits a unified diff into per-file sections
+pub fn split_diff_by_file(diff: &str) -> Vec<FileDiff> {
+    let mut files: Vec<FileDiff> = Vec::new();
+
+    // Lines keep their endings so the sections can be applied again byte for byte
+    for line in diff.split_inclusive('\n') {
+        if line.starts_with("diff --git") {
+            files.push(FileDiff {
+                path: extract_filename_from_diff_header(line.trim_end())
+                    .unwrap_or(line.trim_end())
+                    .to_string(),
+                patch: String::new(),
+            });
+        }
+        if let Some(file) = files.last_mut() {
+            file.patch.push_str(line);
+        }
+    }
+
+    files
+}
+
+/// Counts added and removed lines in one file's patch
+pub fn line_counts(patch: &str) -> (usize, usize) {
+    patch.lines().fold((0, 0), |(added, removed), line| {
+        if line.starts_with('+') && !line.starts_with("+++") {
+            (added + 1, removed)
+        } else if line.starts_with('-') && !line.starts_with("---") {
+            (added, removed + 1)
+        } else {
+            (added, removed)
+        }
+    })
+}
+
+pub fn extract_filename_from_diff_header(header: &str) -> Option<&str> {
+    // Extract filename from "diff --git a/filename b/filename" format
+    if let Some(start) = header.find("a/") {
+        if let Some(end) = header[start + 2..].find(" b/") {
+            return Some(&header[start + 2..start + 2 + end]);
+        }
+    }
+    None
+}
+
+/// Returns the directory git runs hooks from, honouring `core.hooksPath`
+pub fn get_hooks_dir() -> Result<std::path::PathBuf> {
+    let repo = git2::Repository::open_from_env()
+        .map_err(|e| anyhow!("Not inside a git repository: {}", e.message()))?;
+    let hooks_path = repo
+        .config()
+        .and_then(|config| config.get_path("core.hooksPath"))
+        .ok();
+
+    Ok(match hooks_path {
+        // A relative path is relative to where hooks run, the top of the working tree
+        Some(path) => repo.workdir().unwrap_or(repo.path()).join(path),
+        // Worktrees share the hooks of the main repository
+        None => repo.commondir().join("hooks"),
+    })
+}
+
+/// File in the git directory holding a message that could not be committed,
+/// like git's own `COMMIT_EDITMSG`
+const SAVED_MESSAGE_FILE: &str = "COMMAYTE_MSG";
+
+/// Path of a file inside the git directory of the current repository (per worktree)
+pub fn git_dir_path(name: &str) -> Result<std::path::PathBuf> {
+    let repo = git2::Repository::open_from_env()
+        .map_err(|e| anyhow!("Not inside a git repository: {}", e.message()))?;
+    Ok(repo.path().join(name))
+}
+
+/// Root of the working tree of the current repository
+pub fn repo_root() -> Result<std::path::PathBuf> {
+    let repo = git2::Repository::open_from_env()
+        .map_err(|e| anyhow!("Not inside a git repository: {}", e.message()))?;
+    repo.workdir()
+        .map(|dir| dir.to_path_buf())
+        .ok_or_else(|| anyhow!("The repository has no working tree"))
+}
+
+/// Path of the saved message file for the current repository
+fn saved_message_path() -> Result<std::path::PathBuf> {
+    git_dir_path(SAVED_MESSAGE_FILE)
+}
+
+/// Keeps a failed or cancelled message so a later run can offer it again
+pub fn save_message(message: &str) -> Result<()> {
+    std::fs::write(saved_message_path()?, format!("{}\n", message.trim_end()))?;
+    Ok(())
+}
+
+/// Returns the message kept by `save_message`, if any
+pub fn load_saved_message() -> Option<String> {
+    let content = std::fs::read_to_string(saved_message_path().ok()?).ok()?;
+    let message = content.trim();
+    (!message.is_empty()).then(|| message.to_string())
+}
+
+/// Forgets the saved message, once it has been committed or discarded
+pub fn clear_saved_message() {
+    if let Ok(path) = saved_message_path() {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// Executes a git push command to the current branch.
+/// The error includes git's output so rejections can be explained.
+/// Unlike the rest of the git operations this needs the `git` executable, whatever the
+/// backend: libgit2 is built without network transports, and git knows the credentials.
+pub fn execute_git_push() -> Result<()> {
+    if !git_on_path() {
+        return Err(anyhow!(
+            "Pushing needs the git executable on PATH, push from your git client instead"
+        ));
+    }
+    cli::run_git(&["push"], None)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parents in a small history: a ← b ← c ← d, and m merging c and x
+    fn parents_of(id: &str) -> Result<Vec<String>> {
+        let parents: &[&str] = match id {
+            "a" => &[],
+            "b" => &["a"],
+            "c" => &["b"],
+            "d" => &["c"],
+            "m" => &["c", "x"],
+            _ => return Err(anyhow!("unknown commit {id}")),
+        };
+        Ok(parents.iter().map(|parent| parent.to_string()).collect())
+    }
+
+    #[test]
+    fn accepts_a_run_ending_at_head() {
+        assert!(check_linear_run(&["b", "c", "d"], "d", parents_of).is_ok());
+        assert!(check_linear_run(&["a", "b"], "b", parents_of).is_ok());
+    }
+
+    #[test]
+    fn refuses_gaps_merges_and_runs_not_ending_at_head() {
+        assert!(check_linear_run(&["b", "d"], "d", parents_of).is_err());
+        assert!(check_linear_run(&["c", "b"], "b", parents_of).is_err());
+        assert!(check_linear_run(&["m"], "m", parents_of).is_err());
+        assert!(check_linear_run(&["b", "c"], "d", parents_of).is_err());
+    }
+}
@@ -0,0 +1,455 @@
+use anyhow::{anyhow, Result};
+use git2::{
+    ApplyLocation, DescribeFormatOptions, DescribeOptions, Diff, DiffFormat, DiffOptions,
+    Repository, Sort, Status, StatusOptions,
+};
+use std::path::Path;
+
+use super::{ChangeKind, CommitInfo, GitBackend, WorktreeChange};
+
+/// Backend using the bundled libgit2, for machines without `git` on PATH.
+/// Commits made this way skip git hooks and are never signed.
+pub struct NativeBackend {
+    repo: Repository,
+}
+
+impl NativeBackend {
+    /// Opens the repository containing the current directory
+    pub fn open() -> Result<Self> {
+        let repo = Repository::open_from_env()
+            .map_err(|e| anyhow!("Not inside a git repository: {}", e.message()))?;
+        Ok(Self { repo })
+    }
+
+    /// Tree of the HEAD commit, `None` before the first commit
+    fn head_tree(&self) -> Result<Option<git2::Tree<'_>>> {
+        match self.repo.head() {
+            Ok(head) => Ok(Some(head.peel_to_tree()?)),
+            Err(e) if e.code() == git2::ErrorCode::UnbornBranch => Ok(None),
+            Err(e) if e.code() == git2::ErrorCode::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Renders the index changes against `tree` (the empty tree when `None`) as a unified diff
+    fn index_patch(&self, tree: Option<&git2::Tree>, binary: bool) -> Result<String> {
+        let mut options = DiffOptions::new();
+        options.show_binary(binary);
+        let diff = self
+            .repo
+            .diff_tree_to_index(tree, None, Some(&mut options))?;
+        diff_to_string(&diff)
+    }
+
+    fn signature(&self) -> Result<git2::Signature<'static>> {
+        self.repo.signature().map_err(|e| {
+            anyhow!(
+                "Cannot commit without an identity, set user.name and user.email: {}",
+                e.message()
+            )
+        })
+    }
+}
+
+fn commit_info(commit: &git2::Commit) -> CommitInfo {
+    CommitInfo {
+        id: commit.id().to_string(),
+        message: String::from_utf8_lossy(commit.message_bytes())
+            .trim()
+            .to_string(),
+        is_merge: commit.parent_count() > 1,
+        time: commit.time().seconds(),
+    }
+}
+
+/// Renders a diff in the unified format `git diff` prints
+fn diff_to_string(diff: &Diff) -> Result<String> {
+    let mut patch = String::new();
+    diff.print(DiffFormat::Patch, |_, _, line| {
+        // Content lines carry their marker separately from the text
+        if matches!(line.origin(), '+' | '-' | ' ') {
+            patch.push(line.origin());
+        }
+        patch.push_str(&String::from_utf8_lossy(line.content()));
+        true
+    })?;
+    Ok(patch)
+}
+
+impl GitBackend for NativeBackend {
+    fn staged_diff(&self) -> Result<String> {
+        self.index_patch(self.head_tree()?.as_ref(), false)
+    }
+
+    fn amend_diff(&self) -> Result<String> {
+        let head = self
+            .repo
+            .head()
+            .and_then(|head| head.peel_to_commit())
+            .map_err(|_| anyhow!("Nothing to amend, the repository has no commits"))?;
+        let parent_tree = match head.parent(0) {
+            Ok(parent) => Some(parent.tree()?),
+            Err(_) => None,
+        };
+        self.index_patch(parent_tree.as_ref(), false)
+    }
+
+    fn commit(&self, message: &str) -> Result<()> {
+        let signature = self.signature()?;
+
+        let mut index = self.repo.index()?;
+        let tree = self.repo.find_tree(index.write_tree()?)?;
+
+        let parent = match self.repo.head() {
+            Ok(head) => Some(head.peel_to_commit()?),
+            Err(_) => None,
+        };
+        if parent.as_ref().map(|commit| commit.tree_id()) == Some(tree.id()) {
+            return Err(anyhow!("Nothing to commit, the index matches HEAD"));
+        }
+
+        // Same cleanup as the CLI backend: trailing whitespace and extra blank lines go,
+        // lines starting with `#` (e.g. `#123`) stay
+        let message = git2::message_prettify(message, None)?;
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        self.repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            &message,
+            &tree,
+            &parents,
+        )?;
+
+        Ok(())
+    }
+
+    fn amend(&self, message: &str) -> Result<()> {
+        let signature = self.signature()?;
+
+        let head = self
+            .repo
+            .head()
+            .and_then(|head| head.peel_to_commit())
+            .map_err(|_| anyhow!("Nothing to amend, the repository has no commits"))?;
+        let mut index = self.repo.index()?;
+        let tree = self.repo.find_tree(index.write_tree()?)?;
+
+        // Like `git commit --amend`: the author is kept, the committer is updated
+        let message = git2::message_prettify(message, None)?;
+        head.amend(
+            Some("HEAD"),
+            None,
+            Some(&signature),
+            None,
+            Some(&message),
+            Some(&tree),
+        )?;
+
+        Ok(())
+    }
+
+    fn staged_patch(&self) -> Result<String> {
+        // libgit2 only detects renames when asked to, so they already come out split
+        self.index_patch(self.head_tree()?.as_ref(), true)
+    }
+
+    fn reset_index(&self) -> Result<()> {
+        let mut index = self.repo.index()?;
+        match self.head_tree()? {
+            Some(tree) => index.read_tree(&tree)?,
+            None => index.clear()?,
+        }
+        index.write()?;
+        Ok(())
+    }
+
+    fn apply_to_index(&self, patch: &str) -> Result<()> {
+        let diff = Diff::from_buffer(patch.as_bytes())?;
+        self.repo.apply(&diff, ApplyLocation::Index, None)?;
+        Ok(())
+    }
+
+    fn unstaged_changes(&self) -> Result<Vec<WorktreeChange>> {
+        let mut options = StatusOptions::new();
+        options
+            .include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .include_ignored(false);
+
+        let mut changes = Vec::new();
+        for entry in self.repo.statuses(Some(&mut options))?.iter() {
+            let status = entry.status();
+            let kind = if status.contains(Status::WT_NEW) {
+                ChangeKind::Untracked
+            } else if status.contains(Status::WT_DELETED) {
+                ChangeKind::Deleted
+            } else if status
+                .intersects(Status::WT_MODIFIED | Status::WT_TYPECHANGE | Status::WT_RENAMED)
+            {
+                ChangeKind::Modified
+            } else {
+                continue;
+            };
+
+            if let Some(path) = entry.path() {
+                changes.push(WorktreeChange {
+                    path: path.to_string(),
+                    kind,
+                });
+            }
+        }
+
+        Ok(changes)
+    }
+
+    fn stage(&self, paths: &[String]) -> Result<()> {
+        let workdir = self
+            .repo
+            .workdir()
+            .ok_or_else(|| anyhow!("Cannot stage files in a bare repository"))?;
+
+        let mut index = self.repo.index()?;
+        for path in paths {
+            if workdir.join(path).exists() {
+                index.add_path(Path::new(path))?;
+            } else {
+                index.remove_path(Path::new(path))?;
+            }
+        }
+        index.write()?;
+
+        Ok(())
+    }
+
+    fn history(&self, limit: usize) -> Result<Vec<CommitInfo>> {
+        if self.head_tree()?.is_none() {
+            return Ok(Vec::new());
+        }
+
+        let mut walk = self.repo.revwalk()?;
+        walk.push_head()?;
+        walk.set_sorting(Sort::TIME)?;
+
+        let mut commits = Vec::new();
+        for id in walk.take(limit) {
+            commits.push(commit_info(&self.repo.find_commit(id?)?));
+        }
+
+        Ok(commits)
+    }
+
+    fn history_for_paths(&self, paths: &[String], limit: usize) -> Result<Vec<CommitInfo>> {
+        if paths.is_empty() || self.head_tree()?.is_none() {
+            return Ok(Vec::new());
+        }
+
+        let mut walk = self.repo.revwalk()?;
+        walk.push_head()?;
+        walk.set_sorting(Sort::TIME)?;
+
+        let mut commits = Vec::new();
+        // Diffing every commit is slow on long histories, only look at the recent ones
+        for id in walk.take(limit * 20) {
+            let commit = self.repo.find_commit(id?)?;
+            let parent_tree = match commit.parent(0) {
+                Ok(parent) => Some(parent.tree()?),
+                Err(_) => None,
+            };
+
+            let mut options = DiffOptions::new();
+            options.disable_pathspec_match(true);
+            for path in paths {
+                options.pathspec(path);
+            }
+            let diff = self.repo.diff_tree_to_tree(
+                parent_tree.as_ref(),
+                Some(&commit.tree()?),
+                Some(&mut options),
+            )?;
+
+            if diff.deltas().len() > 0 {
+                commits.push(commit_info(&commit));
+                if commits.len() == limit {
+                    break;
+                }
+            }
+        }
+
+        Ok(commits)
+    }
+
+    fn commits_in_range(&self, range: &str) -> Result<Vec<CommitInfo>> {
+        let mut walk = self.repo.revwalk()?;
+        if range.contains("..") {
+            walk.push_range(range)?;
+        } else {
+            walk.push(self.repo.revparse_single(range)?.peel_to_commit()?.id())?;
+        }
+        walk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)?;
+
+        let mut commits = Vec::new();
+        for id in walk {
+            commits.push(commit_info(&self.repo.find_commit(id?)?));
+        }
+
+        Ok(commits)
+    }
+
+    fn previous_tag(&self, rev: &str) -> Result<Option<String>> {
+        // Starting from the parent skips a tag on `rev`; a root commit has no earlier tag
+        let Ok(parent) = self.repo.revparse_single(&format!("{rev}^")) else {
+            return Ok(None);
+        };
+        let Ok(description) = parent.describe(DescribeOptions::new().describe_tags()) else {
+            return Ok(None);
+        };
+        let tag = description.format(Some(DescribeFormatOptions::new().abbreviated_size(0)))?;
+        Ok(Some(tag))
+    }
+
+    fn branch_diff(&self, base: &str) -> Result<String> {
+        let base = self.repo.revparse_single(base)?.peel_to_commit()?;
+        let head = self.repo.head()?.peel_to_commit()?;
+        let fork_point = self
+            .repo
+            .find_commit(self.repo.merge_base(base.id(), head.id())?)?;
+        let diff =
+            self.repo
+                .diff_tree_to_tree(Some(&fork_point.tree()?), Some(&head.tree()?), None)?;
+        diff_to_string(&diff)
+    }
+
+    fn commit_diff(&self, id: &str) -> Result<String> {
+        let commit = self.repo.find_commit(git2::Oid::from_str(id)?)?;
+        let parent_tree = match commit.parent(0) {
+            Ok(parent) => Some(parent.tree()?),
+            Err(_) => None,
+        };
+        let diff =
+            self.repo
+                .diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), None)?;
+        diff_to_string(&diff)
+    }
+
+    fn is_pushed(&self, id: &str) -> Result<bool> {
+        let id = git2::Oid::from_str(id)?;
+        for reference in self.repo.references_glob("refs/remotes/*")? {
+            let Some(target) = reference?.target() else {
+                continue;
+            };
+            if target == id || self.repo.graph_descendant_of(target, id)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn reword(&self, rewrites: &[(String, String)]) -> Result<()> {
+        let Some((first, _)) = rewrites.first() else {
+            return Ok(());
+        };
+        let committer = self.signature()?;
+        let mut head = self.repo.head()?;
+        let old_head = head
+            .target()
+            .ok_or_else(|| anyhow!("HEAD does not point to a commit"))?;
+        let ids: Vec<&str> = rewrites.iter().map(|(id, _)| id.as_str()).collect();
+        super::check_linear_run(&ids, &old_head.to_string(), |id| {
+            let commit = self.repo.find_commit(git2::Oid::from_str(id)?)?;
+            Ok(commit
+                .parent_ids()
+                .map(|parent| parent.to_string())
+                .collect())
+        })?;
+
+        let first = self.repo.find_commit(git2::Oid::from_str(first)?)?;
+        let mut parent = first.parent(0).ok();
+
+        for (id, message) in rewrites {
+            let original = self.repo.find_commit(git2::Oid::from_str(id)?)?;
+            let message = git2::message_prettify(message, None)?;
+            let parents: Vec<&git2::Commit> = parent.iter().collect();
+            let new_id = self.repo.commit(
+                None,
+                &original.author(),
+                &committer,
+                &message,
+                &original.tree()?,
+                &parents,
+            )?;
+            parent = Some(self.repo.find_commit(new_id)?);
+        }
+
+        if self.repo.head()?.target() != Some(old_head) {
+            return Err(anyhow!("HEAD moved while rewording, nothing was changed"));
+        }
+        if let Some(new_head) = parent {
+            head.set_target(new_head.id(), "commayte: reword")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::cli::{run_git, COMMIT_CLEANUP};
+    use super::*;
+    use std::fs;
+
+    fn head_message(repo: &Repository) -> String {
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        head.message().unwrap().to_string()
+    }
+
+    #[test]
+    fn commits_the_same_message_as_the_cli() {
+        let root = std::env::temp_dir().join(format!("commayte-native-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let dir = root.to_str().unwrap();
+        run_git(&["init", "-q", dir], None).unwrap();
+        run_git(&["-C", dir, "config", "user.name", "Test"], None).unwrap();
+        run_git(
+            &["-C", dir, "config", "user.email", "test@example.com"],
+            None,
+        )
+        .unwrap();
+        let message = "fix(parser): keep issue references  \n\n\n\
+            #123 was closed too early\n# not a comment\n\n\n";
+
+        fs::write(root.join("a"), "a").unwrap();
+        run_git(&["-C", dir, "add", "a"], None).unwrap();
+        run_git(
+            &[
+                "-C",
+                dir,
+                "-c",
+                "commit.gpgsign=false",
+                "commit",
+                "-q",
+                COMMIT_CLEANUP,
+                "-F",
+                "-",
+            ],
+            Some(message),
+        )
+        .unwrap();
+        let repo = Repository::open(&root).unwrap();
+        let from_cli = head_message(&repo);
+
+        fs::write(root.join("b"), "b").unwrap();
+        run_git(&["-C", dir, "add", "b"], None).unwrap();
+        let backend = NativeBackend { repo };
+        backend.commit(message).unwrap();
+        let from_native = head_message(&backend.repo);
+
+        assert_eq!(from_native, from_cli);
+        assert!(from_native.contains("\n#123 was closed too early\n# not a comment\n"));
+        // The CLI rewords through `git stripspace`, which must agree too
+        assert_eq!(
+            run_git(&["stripspace"], Some(message)).unwrap(),
+            from_native
+        );
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,699 @@
+use super::FileDiff;
+
+/// Most excluded files listed, a vendored directory can hold thousands
+const MAX_FILES: usize = 30;
+
+/// Most dependency changes listed per file
+const MAX_DEPENDENCY_CHANGES: usize = 20;
+
+/// How a dependency file lists its packages
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DependencyFile {
+    /// Declares the project and what it requires; its own `name` and `version` are not dependencies
+    Manifest,
+    /// Lists resolved packages, each entry starting with a `name` key (`name = "serde"`)
+    NamedLock,
+    /// Lists resolved packages under their name (`"node_modules/react": {`, `react@18.2.0:`)
+    KeyedLock,
+}
+
+/// Manifests and lock files whose diff is read for dependency version changes
+const DEPENDENCY_FILES: &[(&str, DependencyFile)] = &[
+    ("Cargo.toml", DependencyFile::Manifest),
+    ("Cargo.lock", DependencyFile::NamedLock),
+    ("package.json", DependencyFile::Manifest),
+    ("package-lock.json", DependencyFile::KeyedLock),
+    ("npm-shrinkwrap.json", DependencyFile::KeyedLock),
+    ("yarn.lock", DependencyFile::KeyedLock),
+    ("pnpm-lock.yaml", DependencyFile::KeyedLock),
+    ("composer.json", DependencyFile::Manifest),
+    ("composer.lock", DependencyFile::NamedLock),
+    ("go.mod", DependencyFile::Manifest),
+    ("go.sum", DependencyFile::KeyedLock),
+    ("pyproject.toml", DependencyFile::Manifest),
+    ("poetry.lock", DependencyFile::NamedLock),
+    ("uv.lock", DependencyFile::NamedLock),
+    ("Pipfile", DependencyFile::Manifest),
+    ("Gemfile", DependencyFile::Manifest),
+    ("Gemfile.lock", DependencyFile::KeyedLock),
+    ("Podfile.lock", DependencyFile::KeyedLock),
+    ("Cartfile.resolved", DependencyFile::KeyedLock),
+    ("mix.lock", DependencyFile::KeyedLock),
+];
+
+/// Kind of dependency file `file_name` is, `None` for other files
+fn dependency_file(file_name: &str) -> Option<DependencyFile> {
+    if file_name.starts_with("requirements") && file_name.ends_with(".txt") {
+        return Some(DependencyFile::Manifest);
+    }
+    DEPENDENCY_FILES
+        .iter()
+        .find(|(name, _)| *name == file_name)
+        .map(|(_, kind)| *kind)
+}
+
+/// Keys holding versions that are not dependency versions
+const IGNORED_KEYS: &[&str] = &[
+    "specifier",
+    "lockfileVersion",
+    "version_normalized",
+    "lock-version",
+    "python-versions",
+    "requires-python",
+    "revision",
+    "edition",
+    "rust-version",
+];
+
+/// What one line of a manifest or lock file says about dependencies
+enum DependencyLine {
+    /// Starts a package entry keyed by its name, its version follows on another line
+    /// (`"node_modules/react": {`, `react:`)
+    Name(String),
+    /// A `name` key: starts a package entry in named lock files (`name = "serde"`),
+    /// names the project itself in manifests
+    NameKey(String),
+    /// Version of the package entry above (`version = "1.0.200"`)
+    Version(String),
+    /// A package and its version on one line (`"react": "^18.2.0"`)
+    Pinned(String, String),
+}
+
+/// Returns true for requirement values, as opposed to names, paths, scripts or checksums
+fn looks_like_version(value: &str) -> bool {
+    let value = value.trim_start_matches(|c: char| "^~=<>!v* ".contains(c));
+    value.starts_with(|c: char| c.is_ascii_digit())
+        && (value.contains('.') || value.chars().all(|c| c.is_ascii_digit()))
+        && !value.contains(['/', ':'])
+}
+
+/// Removes the quotes around a key or value
+fn unquote(text: &str) -> &str {
+    text.trim().trim_matches(|c| c == '"' || c == '\'')
+}
+
+/// Package name of a `node_modules/foo` path or a `foo@^1.0.0` lock file key
+fn package_name(key: &str) -> &str {
+    let key = key.rsplit("node_modules/").next().unwrap_or(key);
+    let key = key.trim_start_matches('/');
+    match key.rfind('@') {
+        Some(at) if at > 0 => &key[..at],
+        _ => key,
+    }
+}
+
+/// Reads a `key = value` (TOML), `"key": value` (JSON) or `key: value` (YAML) line
+fn parse_key_value(key: &str, value: &str) -> Option<DependencyLine> {
+    let key = unquote(key);
+    let value = value.trim().trim_end_matches(',').trim();
+    if key.contains(' ') || IGNORED_KEYS.contains(&key) {
+        return None;
+    }
+    // The project's own entry in package-lock.json, `"": {`, ends the entry above
+    if key.is_empty() {
+        return value
+            .starts_with('{')
+            .then(|| DependencyLine::Name(String::new()));
+    }
+
+    // Inline tables and objects: `serde = { version = "1.0", features = [...] }`
+    if value.starts_with('{') {
+        let inner = value
+            .split_once("version")
+            .and_then(|(_, rest)| {
+                rest.trim_start_matches(['"', ' ', '=', ':'])
+                    .split(['"', ','])
+                    .next()
+            })
+            .filter(|version| looks_like_version(version));
+        return match inner {
+            Some(version) => Some(DependencyLine::Pinned(key.to_string(), version.to_string())),
+            None => Some(DependencyLine::Name(package_name(key).to_string())),
+        };
+    }
+
+    let value = unquote(value);
+    match key {
+        "name" => Some(DependencyLine::NameKey(value.to_string())),
+        "version" if !value.is_empty() => Some(DependencyLine::Version(value.to_string())),
+        _ if looks_like_version(value) => Some(DependencyLine::Pinned(
+            package_name(key).to_string(),
+            value.to_string(),
+        )),
+        _ => None,
+    }
+}
+
+/// Recognizes the dependency lines of the usual manifest and lock file formats
+fn parse_dependency_line(line: &str) -> Option<DependencyLine> {
+    let line = line.trim().trim_start_matches("- ").trim_end_matches(',');
+    if line.is_empty() || line.starts_with('#') || line.starts_with("//") {
+        return None;
+    }
+
+    // YAML keys: `react:` and `/react@18.2.0:` (pnpm), `"@babel/core@^7.0.0", "@babel/core@^7.1.0":` (yarn)
+    if let Some(key) = line.strip_suffix(':') {
+        // pnpm appends peer dependencies: `/react-dom@18.2.0(react@18.2.0)`
+        let key = unquote(key.split(", ").next().unwrap_or(key))
+            .split('(')
+            .next()
+            .unwrap_or("")
+            .trim_start_matches('/');
+        if key.contains(' ') {
+            return None;
+        }
+        let name = package_name(key);
+        let version = key.strip_prefix(name).unwrap_or("").trim_start_matches('@');
+        // yarn keys hold the requested range, the resolved version follows on its own line
+        return Some(
+            if version.starts_with(|c: char| c.is_ascii_digit()) && looks_like_version(version) {
+                DependencyLine::Pinned(name.to_string(), version.to_string())
+            } else {
+                DependencyLine::Name(name.to_string())
+            },
+        );
+    }
+    if let Some((key, value)) = line.split_once(" = ") {
+        return parse_key_value(key, value);
+    }
+    if line.starts_with('"') {
+        if let Some((key, value)) = line.split_once("\": ") {
+            return parse_key_value(key, value);
+        }
+    }
+    if let Some((key, value)) = line.split_once(": ") {
+        return parse_key_value(key, value);
+    }
+    // yarn v1: `version "1.2.3"`
+    if let Some(version) = line.strip_prefix("version ") {
+        return Some(DependencyLine::Version(unquote(version).to_string()));
+    }
+    // Gemfile: `gem "rails", "~> 7.0"`
+    if let Some(rest) = line.strip_prefix("gem ") {
+        let mut parts = rest.split(',').map(unquote);
+        let (name, version) = (parts.next()?, parts.next()?);
+        return looks_like_version(version)
+            .then(|| DependencyLine::Pinned(name.to_string(), version.to_string()));
+    }
+    // Gemfile.lock and Podfile.lock: `rails (7.0.8)`
+    if let Some((name, version)) = line.strip_suffix(')').and_then(|l| l.split_once(" (")) {
+        return version
+            .starts_with(|c: char| c.is_ascii_digit())
+            .then(|| DependencyLine::Pinned(name.to_string(), version.to_string()));
+    }
+
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    match tokens.as_slice() {
+        // go.mod and go.sum: `github.com/pkg/errors v0.9.1 // indirect`
+        ["require", name, version, ..] | [name, version, ..]
+            if version.starts_with('v')
+                && looks_like_version(version.trim_end_matches("/go.mod"))
+                && name.contains('/') =>
+        {
+            Some(DependencyLine::Pinned(
+                name.to_string(),
+                version.trim_end_matches("/go.mod").to_string(),
+            ))
+        }
+        // Cartfile.resolved: `github "Alamofire/Alamofire" "5.8.1"`
+        [_, name, version] if looks_like_version(unquote(version)) => Some(DependencyLine::Pinned(
+            unquote(name).to_string(),
+            unquote(version).to_string(),
+        )),
+        // requirements.txt: `requests[socks]==2.31.0 ; python_version >= "3.8"`
+        _ => {
+            let requirement = unquote(line.split(';').next().unwrap_or(line));
+            let split = requirement.find(['=', '<', '>', '~', '!'])?;
+            let name = requirement[..split].split('[').next()?.trim();
+            let version = requirement[split..].trim_start_matches("==").trim();
+            (!name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || "-_.".contains(c))
+                && looks_like_version(version))
+            .then(|| DependencyLine::Pinned(name.to_string(), version.to_string()))
+        }
+    }
+}
+
+/// Part of a manifest a line is in, as far as the hunk shows
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Section {
+    /// No section start seen yet in the hunk
+    Unknown,
+    /// A list of dependencies: `[dependencies]`, `"devDependencies": {`, `dependencies = [`
+    Dependencies,
+    /// A table describing one dependency, `[dependencies.serde]`
+    Dependency(String),
+    /// Anything else: `[package]`, `[profile.release]`, `"scripts": {`
+    Other,
+}
+
+/// Returns true for the names of dependency lists in the usual manifests
+fn is_dependency_section(name: &str) -> bool {
+    let name = unquote(name).to_lowercase();
+    name.ends_with("dependencies")
+        || name.ends_with("packages")
+        || name == "require"
+        || name == "require-dev"
+}
+
+/// Section a manifest line starts, `None` for the lines inside a section
+fn section_start(line: &str) -> Option<Section> {
+    let line = line.trim();
+
+    // TOML tables: `[dev-dependencies]`, `[target.'cfg(unix)'.dependencies]`, `[dependencies.serde]`
+    if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+        let parts: Vec<&str> = header.trim_matches(['[', ']']).split('.').collect();
+        return Some(
+            match parts.iter().position(|part| is_dependency_section(part)) {
+                Some(index) => match parts.get(index + 1) {
+                    Some(name) => Section::Dependency(unquote(name).to_string()),
+                    None => Section::Dependencies,
+                },
+                None => Section::Other,
+            },
+        );
+    }
+    // JSON objects and TOML arrays: `"require-dev": {`, `dependencies = [`
+    if let Some(key) = line
+        .strip_suffix('{')
+        .or_else(|| line.strip_suffix('['))
+        .and_then(|rest| rest.trim_end().strip_suffix([':', '=']))
+    {
+        return Some(if is_dependency_section(key.trim()) {
+            Section::Dependencies
+        } else {
+            Section::Other
+        });
+    }
+    // The end of a list: what follows belongs to no dependency list
+    if matches!(line, "}" | "}," | "]" | "],") {
+        return Some(Section::Other);
+    }
+    None
+}
+
+/// Lists the dependency versions a manifest or lock file diff removes and adds,
+/// as `serde 1.0.199 → 1.0.200`, `tokio 1.38.0 (added)` or `log 0.4.21 (removed)`
+fn dependency_changes(kind: DependencyFile, patch: &str) -> Vec<String> {
+    // Versions per package, in the order packages first appear
+    let mut names: Vec<String> = Vec::new();
+    let mut removed: Vec<(String, String)> = Vec::new();
+    let mut added: Vec<(String, String)> = Vec::new();
+    // Package entry each side of the diff is in, for versions on their own line
+    let mut old_name: Option<String> = None;
+    let mut new_name: Option<String> = None;
+    let mut section = Section::Unknown;
+
+    let hunks = patch.lines().skip_while(|line| !line.starts_with("@@"));
+    for line in hunks {
+        if line.starts_with("@@") {
+            old_name = None;
+            new_name = None;
+            section = Section::Unknown;
+            continue;
+        }
+        let (side, content) = match line.chars().next() {
+            Some(side @ ('+' | '-' | ' ')) => (side, &line[1..]),
+            _ => continue,
+        };
+
+        let mut parsed = parse_dependency_line(content);
+        if kind == DependencyFile::Manifest {
+            if let Some(start) = section_start(content) {
+                section = start;
+                if let Section::Dependency(name) = &section {
+                    parsed = Some(DependencyLine::Name(name.clone()));
+                } else {
+                    continue;
+                }
+            } else {
+                // Outside dependency lists, and for the project's own `name` and `version`,
+                // nothing is a dependency; a version only belongs to a `[dependencies.x]` table
+                parsed = match (&section, parsed) {
+                    (Section::Other, _)
+                    | (_, Some(DependencyLine::Name(_) | DependencyLine::NameKey(_))) => None,
+                    (Section::Dependency(_), version @ Some(DependencyLine::Version(_))) => version,
+                    (_, Some(DependencyLine::Version(_))) => None,
+                    (_, parsed) => parsed,
+                };
+            }
+        }
+
+        let (name, version) = match parsed {
+            Some(DependencyLine::NameKey(_)) if kind != DependencyFile::NamedLock => continue,
+            Some(DependencyLine::Name(name) | DependencyLine::NameKey(name)) => {
+                let name = Some(name).filter(|name| !name.is_empty());
+                if side != '+' {
+                    old_name = name.clone();
+                }
+                if side != '-' {
+                    new_name = name;
+                }
+                continue;
+            }
+            Some(DependencyLine::Version(version)) => {
+                let name = if side == '-' { &old_name } else { &new_name };
+                match name {
+                    Some(name) => (name.clone(), version),
+                    None => continue,
+                }
+            }
+            Some(DependencyLine::Pinned(name, version)) => (name, version),
+            None => continue,
+        };
+
+        let target = match side {
+            '-' => &mut removed,
+            '+' => &mut added,
+            _ => continue,
+        };
+        if !names.contains(&name) {
+            names.push(name.clone());
+        }
+        if !target.contains(&(name.clone(), version.clone())) {
+            target.push((name, version));
+        }
+    }
+
+    let versions = |list: &[(String, String)], other: &[(String, String)], name: &str| {
+        list.iter()
+            .filter(|(n, v)| n == name && !other.iter().any(|(on, ov)| on == name && ov == v))
+            .map(|(_, v)| v.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    names
+        .iter()
+        .filter_map(|name| {
+            let old = versions(&removed, &added, name);
+            let new = versions(&added, &removed, name);
+            match (old.is_empty(), new.is_empty()) {
+                (false, false) => Some(format!("{name} {old} → {new}")),
+                (true, false) => Some(format!("{name} {new} (added)")),
+                (false, true) => Some(format!("{name} {old} (removed)")),
+                (true, true) => None,
+            }
+        })
+        .collect()
+}
+
+/// One line describing an excluded file: path, change type and size of the change,
+/// followed by the dependency versions it changes for manifests and lock files
+fn summarize_file(file: &FileDiff) -> String {
+    let mut path = file.path.clone();
+    let mut change = "modified";
+    let mut binary = false;
+    for line in file
+        .patch
+        .lines()
+        .take_while(|line| !line.starts_with("@@"))
+    {
+        if line.starts_with("new file mode") {
+            change = "added";
+        } else if line.starts_with("deleted file mode") {
+            change = "deleted";
+        } else if let Some(to) = line.strip_prefix("rename to ") {
+            change = "renamed";
+            path = format!("{} → {to}", file.path);
+        } else if line.starts_with("Binary file") || line.starts_with("GIT binary patch") {
+            binary = true;
+        }
+    }
+
+    let mut summary = if binary {
+        format!("- {path}: {change}, binary")
+    } else {
+        let (insertions, deletions) = super::line_counts(&file.patch);
+        format!("- {path}: {change}, +{insertions} -{deletions}")
+    };
+
+    let file_name = file.path.rsplit('/').next().unwrap_or(&file.path);
+    if let Some(kind) = dependency_file(file_name).filter(|_| !binary) {
+        let changes = dependency_changes(kind, &file.patch);
+        if !changes.is_empty() {
+            let shown = changes.len().min(MAX_DEPENDENCY_CHANGES);
+            summary.push_str(&format!(
+                "\n  dependencies: {}",
+                changes[..shown].join(", ")
+            ));
+            if changes.len() > shown {
+                summary.push_str(&format!(" and {} more", changes.len() - shown));
+            }
+        }
+    }
+    summary
+}
+
+/// Describes the files left out of the diff, so the model still knows they changed
+pub(super) fn summarize_excluded(files: &[FileDiff]) -> String {
+    let mut summary = String::from(
+        "Files left out of the diff (lock files, generated or binary files), summarized:\n",
+    );
+    for file in files.iter().take(MAX_FILES) {
+        summary.push_str(&summarize_file(file));
+        summary.push('\n');
+    }
+    if files.len() > MAX_FILES {
+        summary.push_str(&format!(
+            "- ... and {} more files\n",
+            files.len() - MAX_FILES
+        ));
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Dependency changes of a one-hunk diff of `file_name`
+    fn changes(file_name: &str, hunk: &str) -> Vec<String> {
+        let patch = format!(
+            "diff --git a/{file_name} b/{file_name}\n--- a/{file_name}\n+++ b/{file_name}\n@@ -1,10 +1,10 @@\n{hunk}"
+        );
+        dependency_changes(dependency_file(file_name).unwrap(), &patch)
+    }
+
+    #[test]
+    fn package_json_skips_the_project_itself() {
+        let hunk = r#" {
+   "name": "app",
+-  "version": "1.0.0",
++  "version": "1.1.0",
+   "engines": {
+-    "node": ">=16"
++    "node": ">=18"
+   },
+   "dependencies": {
+-    "lodash": "^4.17.20",
++    "lodash": "^4.17.21",
+     "react": "^18.2.0"
+   },
+   "devDependencies": {
++    "vitest": "^1.6.0"
+   }
+ }
+"#;
+        assert_eq!(
+            changes("package.json", hunk),
+            ["lodash ^4.17.20 → ^4.17.21", "vitest ^1.6.0 (added)"]
+        );
+    }
+
+    #[test]
+    fn package_json_hunk_without_a_section() {
+        let hunk = r#"   "name": "app",
+-  "version": "2.0.0",
++  "version": "2.0.1",
+   "private": true,
+"#;
+        assert!(changes("package.json", hunk).is_empty());
+    }
+
+    #[test]
+    fn composer_json_reads_require_sections() {
+        let hunk = r#" {
+     "name": "acme/app",
+-    "version": "1.0.0",
++    "version": "1.1.0",
+     "require": {
+-        "monolog/monolog": "^2.9",
++        "monolog/monolog": "^3.0",
+         "php": ">=8.1"
+     },
+     "require-dev": {
+-        "phpunit/phpunit": "^10.0"
++        "phpunit/phpunit": "^11.0"
+     }
+ }
+"#;
+        assert_eq!(
+            changes("composer.json", hunk),
+            [
+                "monolog/monolog ^2.9 → ^3.0",
+                "phpunit/phpunit ^10.0 → ^11.0"
+            ]
+        );
+    }
+
+    #[test]
+    fn cargo_toml_reads_only_dependency_tables() {
+        let hunk = r#" [package]
+ name = "app"
+-version = "0.3.0"
++version = "0.4.0"
+ edition = "2021"
+ 
+ [dependencies]
+-serde = "1.0.199"
++serde = "1.0.200"
+-anyhow = { version = "1.0.80", features = ["backtrace"] }
++anyhow = { version = "1.0.86", features = ["backtrace"] }
+ 
+ [dependencies.tokio]
+-version = "1.37.0"
++version = "1.38.0"
+ features = ["full"]
+ 
+ [target.'cfg(unix)'.dev-dependencies]
++nix = "0.29"
+ 
+ [profile.release]
+-opt-level = 2
++opt-level = 3
+"#;
+        assert_eq!(
+            changes("Cargo.toml", hunk),
+            [
+                "serde 1.0.199 → 1.0.200",
+                "anyhow 1.0.80 → 1.0.86",
+                "tokio 1.37.0 → 1.38.0",
+                "nix 0.29 (added)"
+            ]
+        );
+    }
+
+    #[test]
+    fn pyproject_reads_dependency_arrays_and_tables() {
+        let hunk = r#" [project]
+ name = "app"
+-version = "0.1.0"
++version = "0.2.0"
+ dependencies = [
+-    "requests>=2.31.0",
++    "requests>=2.32.0",
+ ]
+ 
+ [tool.poetry.dependencies]
+-httpx = "^0.26"
++httpx = "^0.27"
+ 
+ [tool.black]
+-line-length = 88
++line-length = 100
+"#;
+        assert_eq!(
+            changes("pyproject.toml", hunk),
+            ["requests >=2.31.0 → >=2.32.0", "httpx ^0.26 → ^0.27"]
+        );
+    }
+
+    #[test]
+    fn requirements_gemfile_and_go_mod() {
+        assert_eq!(
+            changes(
+                "requirements-dev.txt",
+                "-pytest==7.4.0\n+pytest==8.2.0\n requests[socks]==2.31.0 ; python_version >= \"3.8\"\n"
+            ),
+            ["pytest 7.4.0 → 8.2.0"]
+        );
+        assert_eq!(
+            changes(
+                "Gemfile",
+                " source \"https://rubygems.org\"\n-gem \"rails\", \"~> 7.0\"\n+gem \"rails\", \"~> 7.1\"\n"
+            ),
+            ["rails ~> 7.0 → ~> 7.1"]
+        );
+        assert_eq!(
+            changes(
+                "go.mod",
+                " module example.com/app\n \n-require github.com/pkg/errors v0.9.0\n+require github.com/pkg/errors v0.9.1\n"
+            ),
+            ["github.com/pkg/errors v0.9.0 → v0.9.1"]
+        );
+    }
+
+    #[test]
+    fn cargo_lock_entries_start_with_their_name() {
+        let hunk = r#" [[package]]
+ name = "serde"
+-version = "1.0.199"
++version = "1.0.200"
+ source = "registry+https://github.com/rust-lang/crates.io-index"
++
++[[package]]
++name = "tokio"
++version = "1.38.0"
+"#;
+        assert_eq!(
+            changes("Cargo.lock", hunk),
+            ["serde 1.0.199 → 1.0.200", "tokio 1.38.0 (added)"]
+        );
+    }
+
+    #[test]
+    fn package_lock_skips_the_project_itself() {
+        let hunk = r#" {
+   "name": "app",
+-  "version": "1.0.0",
++  "version": "1.1.0",
+   "lockfileVersion": 3,
+   "packages": {
+     "": {
+       "name": "app",
+-      "version": "1.0.0",
++      "version": "1.1.0",
+     },
+     "node_modules/lodash": {
+-      "version": "4.17.20",
++      "version": "4.17.21",
+"#;
+        assert_eq!(
+            changes("package-lock.json", hunk),
+            ["lodash 4.17.20 → 4.17.21"]
+        );
+    }
+
+    #[test]
+    fn yarn_pnpm_and_gemfile_locks() {
+        assert_eq!(
+            changes(
+                "yarn.lock",
+                " \"@babel/core@^7.0.0\", \"@babel/core@^7.1.0\":\n-  version \"7.24.0\"\n+  version \"7.24.5\"\n"
+            ),
+            ["@babel/core 7.24.0 → 7.24.5"]
+        );
+        assert_eq!(
+            changes("pnpm-lock.yaml", "-  /react@18.2.0:\n+  /react@18.3.1:\n"),
+            ["react 18.2.0 → 18.3.1"]
+        );
+        assert_eq!(
+            changes("Gemfile.lock", "-    rails (7.0.8)\n+    rails (7.1.3)\n"),
+            ["rails 7.0.8 → 7.1.3"]
+        );
+    }
+
+    #[test]
+    fn summarizes_an_excluded_file() {
+        let file = FileDiff {
+            path: "web/package.json".to_string(),
+            patch: "diff --git a/web/package.json b/web/package.json\n--- a/web/package.json\n+++ b/web/package.json\n@@ -1,4 +1,4 @@\n   \"dependencies\": {\n-    \"lodash\": \"^4.17.20\"\n+    \"lodash\": \"^4.17.21\"\n   }\n".to_string(),
+        };
+        assert_eq!(
+            summarize_file(&file),
+            "- web/package.json: modified, +1 -1\n  dependencies: lodash ^4.17.20 → ^4.17.21"
+        );
+    }
+}
@@ -0,0 +1,169 @@
+use anyhow::{anyhow, Result};
+use clap::Subcommand;
+use colored::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::client;
+use crate::config;
+use crate::git;
+
+/// Hook name git calls before opening the editor on `git commit`
+const HOOK_NAME: &str = "prepare-commit-msg";
+
+/// Line identifying a hook script written by Commayte, so others are never touched
+const HOOK_MARKER: &str = "# Installed by commayte";
+
+/// Commit sources (second hook argument) that already come with a message
+const SKIPPED_SOURCES: [&str; 4] = ["message", "merge", "squash", "commit"];
+
+#[derive(Subcommand)]
+pub enum HookAction {
+    /// Install the prepare-commit-msg hook in the current repository
+    Install {
+        /// Add emojis to the generated messages
+        #[arg(long)]
+        emoji: bool,
+        /// Replace an existing hook that was not installed by Commayte
+        #[arg(long)]
+        force: bool,
+    },
+    /// Remove the hook installed by `commayte hook install`
+    Uninstall,
+    /// Fill the commit message file (called by git)
+    Run {
+        /// Path of the commit message file
+        file: PathBuf,
+        /// Where the message comes from: message, template, merge, squash or commit
+        source: Option<String>,
+        /// Commit SHA, given when amending or reusing a commit
+        sha: Option<String>,
+        /// Add emojis to the generated message
+        #[arg(long)]
+        emoji: bool,
+    },
+}
+
+pub fn run(action: &HookAction, cli_overrides: &[(&str, String)]) -> Result<()> {
+    match action {
+        HookAction::Install { emoji, force } => install(*emoji, *force),
+        HookAction::Uninstall => uninstall(),
+        HookAction::Run {
+            file,
+            source,
+            emoji,
+            ..
+        } => {
+            // A hook must never block the commit: report problems and let git continue
+            if let Err(e) = run_hook(file, source.as_deref(), *emoji, cli_overrides) {
+                eprintln!(
+                    "{} {}",
+                    "⚠️  Commayte could not generate a message:".yellow(),
+                    e
+                );
+            }
+            Ok(())
+        }
+    }
+}
+
+fn hook_path() -> Result<PathBuf> {
+    Ok(git::get_hooks_dir()?.join(HOOK_NAME))
+}
+
+/// Returns true when the file at `path` was written by `install`
+fn is_commayte_hook(path: &Path) -> bool {
+    fs::read_to_string(path)
+        .map(|content| content.contains(HOOK_MARKER))
+        .unwrap_or(false)
+}
+
+fn install(emoji: bool, force: bool) -> Result<()> {
+    let path = hook_path()?;
+
+    if path.exists() && !is_commayte_hook(&path) && !force {
+        return Err(anyhow!(
+            "A {HOOK_NAME} hook already exists at {}\nUse --force to replace it",
+            path.display()
+        ));
+    }
+
+    // Call this binary by its full path, IDEs often run git with a minimal PATH
+    let executable = std::env::current_exe()?;
+    let emoji_flag = if emoji { " --emoji" } else { "" };
+    let script = format!(
+        "#!/bin/sh\n{HOOK_MARKER}, remove with `commayte hook uninstall`\nexec \"{}\" hook run{emoji_flag} \"$@\"\n",
+        executable.display()
+    );
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, script)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+    }
+
+    println!(
+        "✅ Installed {} hook at {}",
+        HOOK_NAME.bold(),
+        path.display()
+    );
+    Ok(())
+}
+
+fn uninstall() -> Result<()> {
+    let path = hook_path()?;
+
+    if !path.exists() {
+        println!("{}", "⚠️  No prepare-commit-msg hook installed.".yellow());
+        return Ok(());
+    }
+    if !is_commayte_hook(&path) {
+        return Err(anyhow!(
+            "The hook at {} was not installed by Commayte, leaving it alone",
+            path.display()
+        ));
+    }
+
+    fs::remove_file(&path)?;
+    println!("✅ Removed {} hook", HOOK_NAME.bold());
+    Ok(())
+}
+
+fn run_hook(
+    file: &Path,
+    source: Option<&str>,
+    use_emoji: bool,
+    cli_overrides: &[(&str, String)],
+) -> Result<()> {
+    // -m/-F, merges, squashes and amends already have a message worth keeping
+    if source.is_some_and(|source| SKIPPED_SOURCES.contains(&source)) {
+        return Ok(());
+    }
+
+    let configuration = config::load_config(cli_overrides);
+    let backend = git::open_backend(&configuration.git)?;
+    let Some(prompt) = client::build_prompt(backend.as_ref(), use_emoji, false, &configuration)?
+    else {
+        return Ok(());
+    };
+
+    eprintln!("{}", "🤖 Generating commit message...".dimmed());
+    let Some(message) = client::generate_valid_message(&prompt, &configuration, use_emoji)? else {
+        // Leave the message to the user rather than failing the commit
+        eprintln!(
+            "{}",
+            "⚠️  No valid commit message was generated, write your own.".yellow()
+        );
+        return Ok(());
+    };
+
+    // Keep git's comments (and any template) below the generated message
+    let existing = fs::read_to_string(file).unwrap_or_default();
+    fs::write(file, format!("{message}\n{existing}"))?;
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use anyhow::{anyhow, Result};
+use colored::*;
+use std::path::{Component, Path, PathBuf};
+
+use crate::config;
+use crate::git;
+
+/// Resolves `path` (relative to the current directory) to a path relative to the
+/// repository root, without requiring the file to exist
+fn repo_relative(path: &Path) -> Result<String> {
+    let root = git::repo_root()?.canonicalize()?;
+    let absolute = std::env::current_dir()?.canonicalize()?.join(path);
+
+    let mut normalized = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+
+    let relative = normalized
+        .strip_prefix(&root)
+        .map_err(|_| anyhow!("{} is outside the repository", path.display()))?;
+    Ok(relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/"))
+}
+
+/// Prints whether a path would be sent to the model, and which rule decided it
+pub fn explain(configuration: &config::Config, path: &Path) -> Result<()> {
+    let relative = repo_relative(path)?;
+    if relative.is_empty() {
+        return Err(anyhow!("Give a file path inside the repository"));
+    }
+    let is_dir = path.is_dir();
+
+    let rules = git::IgnoreRules::load(&configuration.ignore);
+    match rules.explain(&relative, is_dir) {
+        Some(rule) if rule.ignored => println!(
+            "🙈 {} is ignored by {} ({})",
+            relative.bold(),
+            rule.pattern.yellow(),
+            rule.source.dimmed()
+        ),
+        Some(rule) => println!(
+            "✅ {} is included by {} ({})",
+            relative.bold(),
+            rule.pattern.green(),
+            rule.source.dimmed()
+        ),
+        None => println!("✅ {} is not ignored, no rule matches it", relative.bold()),
+    }
+    Ok(())
+}
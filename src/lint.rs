@@ -0,0 +1,419 @@
+use anyhow::{anyhow, Result};
+use colored::*;
+use std::fs;
+use std::path::Path;
+
+use crate::ai;
+use crate::config::{self, SubjectCase};
+use crate::conventional::{ConventionalCommit, ParseError};
+use crate::git;
+
+/// Every rule `lint` checks, named like their commitlint counterparts
+pub const RULES: &[&str] = &[
+    "header-format",
+    "header-max-length",
+    "type-empty",
+    "type-case",
+    "type-enum",
+    "scope-enum",
+    "subject-empty",
+    "subject-case",
+    "subject-full-stop",
+    "body-leading-blank",
+    "body-max-line-length",
+];
+
+/// Line below which git drops everything when committing with `--verbose`
+const SCISSORS: &str = "# ------------------------ >8 ------------------------";
+
+/// A broken rule and what is wrong
+#[derive(Debug)]
+pub struct Violation {
+    pub rule: &'static str,
+    pub message: String,
+}
+
+/// Messages git or other tools generate, which are not expected to be conventional
+fn is_ignored(message: &str) -> bool {
+    ["Merge ", "Revert \"", "fixup! ", "squash! ", "amend! "]
+        .iter()
+        .any(|prefix| message.starts_with(prefix))
+}
+
+/// Checks one message against every enabled rule
+pub fn lint_message(message: &str, configuration: &config::Config) -> Vec<Violation> {
+    let rules = &configuration.lint;
+    let mut violations = Vec::new();
+    let mut report = |rule: &'static str, message: String| {
+        if !rules.disabled.iter().any(|disabled| disabled == rule) {
+            violations.push(Violation { rule, message });
+        }
+    };
+
+    let mut lines = message.trim().lines();
+    let header = lines.next().unwrap_or("").trim();
+
+    let length = header.chars().count();
+    if length > rules.header_max_length {
+        report(
+            "header-max-length",
+            format!(
+                "header is {length} characters, the limit is {}",
+                rules.header_max_length
+            ),
+        );
+    }
+
+    if lines.next().is_some_and(|line| !line.trim().is_empty()) {
+        report(
+            "body-leading-blank",
+            "body must be separated from the header by a blank line".to_string(),
+        );
+    }
+    for (number, line) in message.trim().lines().enumerate().skip(1) {
+        let length = line.chars().count();
+        if length > rules.body_max_line_length {
+            report(
+                "body-max-line-length",
+                format!(
+                    "line {} is {length} characters, the limit is {}",
+                    number + 1,
+                    rules.body_max_line_length
+                ),
+            );
+        }
+    }
+
+    let (_, bare_header) = ai::split_leading_emoji(header);
+    let commit = match ConventionalCommit::parse_header(bare_header) {
+        Ok(commit) => commit,
+        Err(e) => {
+            let rule = match e {
+                ParseError::Empty | ParseError::EmptyDescription => "subject-empty",
+                ParseError::MissingType => "type-empty",
+                _ => "header-format",
+            };
+            report(rule, format!("{e} (expected 'type(scope): subject')"));
+            return violations;
+        }
+    };
+
+    if commit.commit_type != commit.commit_type.to_lowercase() {
+        report(
+            "type-case",
+            format!("type '{}' must be lowercase", commit.commit_type),
+        );
+    }
+    if let Err(e) = commit.check_type(&configuration.commit.type_names()) {
+        report(
+            "type-enum",
+            format!(
+                "{e}, use one of: {}",
+                configuration.commit.type_names().join(", ")
+            ),
+        );
+    }
+    if let Err(e) = commit.check_scope(&configuration.commit.scope_names()) {
+        report(
+            "scope-enum",
+            format!(
+                "{e}, use one of: {}",
+                configuration.commit.scope_names().join(", ")
+            ),
+        );
+    }
+
+    if let Some(first) = commit.description.chars().next() {
+        match rules.subject_case {
+            SubjectCase::Lower if first.is_uppercase() => report(
+                "subject-case",
+                "subject must start with a lowercase letter".to_string(),
+            ),
+            SubjectCase::Sentence if first.is_lowercase() => report(
+                "subject-case",
+                "subject must start with an uppercase letter".to_string(),
+            ),
+            _ => {}
+        }
+    }
+    if commit.description.ends_with('.') {
+        report(
+            "subject-full-stop",
+            "subject must not end with '.'".to_string(),
+        );
+    }
+
+    violations
+}
+
+/// Reads a commit message file the way git does: comments and the verbose diff are dropped
+fn read_message_file(path: &Path) -> Result<String> {
+    let content =
+        fs::read_to_string(path).map_err(|e| anyhow!("Could not read {}: {e}", path.display()))?;
+    let content = content.split(SCISSORS).next().unwrap_or("");
+    Ok(content
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string())
+}
+
+/// Prints the result for one message, returns the number of violations
+fn print_report(label: &str, message: &str, configuration: &config::Config) -> usize {
+    let subject = message.lines().next().unwrap_or("");
+    if is_ignored(message) {
+        println!("{} {} {}", "⏭️ ".dimmed(), label.yellow(), subject.dimmed());
+        return 0;
+    }
+
+    let violations = lint_message(message, configuration);
+    if violations.is_empty() {
+        println!("✅ {} {}", label.yellow(), subject);
+    } else {
+        println!("❌ {} {}", label.yellow(), subject.bold());
+        for violation in &violations {
+            println!(
+                "   {} {} {}",
+                "✖".red(),
+                format!("{}:", violation.rule).red(),
+                violation.message
+            );
+        }
+    }
+    violations.len()
+}
+
+pub fn run(
+    configuration: &config::Config,
+    from: Option<&str>,
+    to: &str,
+    edit: Option<&Path>,
+) -> Result<()> {
+    let unknown: Vec<&str> = configuration
+        .lint
+        .disabled
+        .iter()
+        .map(String::as_str)
+        .filter(|rule| !RULES.contains(rule))
+        .collect();
+    if !unknown.is_empty() {
+        eprintln!(
+            "{} {}",
+            "⚠️  Unknown rules in lint.disabled:".yellow(),
+            unknown.join(", ")
+        );
+    }
+
+    let (problems, failed, checked) = match edit {
+        Some(path) => {
+            let message = read_message_file(path)?;
+            if message.trim().is_empty() {
+                // git aborts empty commits by itself
+                return Ok(());
+            }
+            let problems = print_report("message", &message, configuration);
+            (problems, usize::from(problems > 0), 1)
+        }
+        None => {
+            let backend = git::open_backend(&configuration.git)?;
+            let commits: Vec<git::CommitInfo> = match from {
+                Some(from) => backend.commits_in_range(&format!("{from}..{to}"))?,
+                None => backend.history(1)?,
+            }
+            .into_iter()
+            .filter(|commit| !commit.is_merge)
+            .collect();
+
+            let mut problems = 0;
+            let mut failed = 0;
+            for commit in &commits {
+                let label = &commit.id[..commit.id.len().min(7)];
+                let found = print_report(label, &commit.message, configuration);
+                problems += found;
+                failed += usize::from(found > 0);
+            }
+            (problems, failed, commits.len())
+        }
+    };
+
+    println!();
+    if problems == 0 {
+        println!(
+            "{}",
+            format!("✅ {checked} message(s) checked, no problems").green()
+        );
+        return Ok(());
+    }
+    Err(anyhow!(
+        "{problems} problem(s) in {failed} of {checked} message(s)"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Rules `message` breaks with `configuration`
+    fn broken(message: &str, configuration: &config::Config) -> Vec<&'static str> {
+        lint_message(message, configuration)
+            .into_iter()
+            .map(|violation| violation.rule)
+            .collect()
+    }
+
+    fn broken_by_default(message: &str) -> Vec<&'static str> {
+        broken(message, &config::Config::default())
+    }
+
+    /// Runs `lint --edit` on a message file of its own
+    fn run_on_file(name: &str, content: &str) -> Result<()> {
+        let path =
+            std::env::temp_dir().join(format!("commayte-lint-{}-{name}", std::process::id()));
+        fs::write(&path, content).unwrap();
+        let result = run(&config::Config::default(), None, "HEAD", Some(&path));
+        fs::remove_file(&path).unwrap();
+        result
+    }
+
+    #[test]
+    fn accepts_a_valid_message() {
+        assert!(broken_by_default("feat(api): add pagination").is_empty());
+        assert!(broken_by_default("✨ feat: add pagination\n\nWith a cursor.").is_empty());
+    }
+
+    #[test]
+    fn header_max_length() {
+        let header = format!("feat: {}", "a".repeat(66));
+        assert!(broken_by_default(&header).is_empty());
+        assert_eq!(
+            broken_by_default(&format!("{header}b")),
+            ["header-max-length"]
+        );
+
+        // Counted in characters, not bytes
+        let accented = format!("feat: {}", "é".repeat(66));
+        assert!(broken_by_default(&accented).is_empty());
+
+        let mut configuration = config::Config::default();
+        configuration.lint.header_max_length = 20;
+        assert_eq!(
+            broken("feat: add pagination to lists", &configuration),
+            ["header-max-length"]
+        );
+    }
+
+    #[test]
+    fn header_format_and_empty_parts() {
+        assert_eq!(broken_by_default("add pagination"), ["header-format"]);
+        assert_eq!(broken_by_default("feat:add pagination"), ["header-format"]);
+        assert_eq!(broken_by_default(": add pagination"), ["type-empty"]);
+        assert_eq!(broken_by_default("feat: "), ["subject-empty"]);
+    }
+
+    #[test]
+    fn type_enum_and_case() {
+        assert_eq!(broken_by_default("feature: add pagination"), ["type-enum"]);
+        assert_eq!(
+            broken_by_default("Feat: add pagination"),
+            ["type-case", "type-enum"]
+        );
+
+        let mut configuration = config::Config::default();
+        configuration.commit.types.retain(|t| t.name == "fix");
+        assert_eq!(
+            broken("feat: add pagination", &configuration),
+            ["type-enum"]
+        );
+    }
+
+    #[test]
+    fn scope_enum() {
+        // Without configured scopes any scope is accepted
+        assert!(broken_by_default("feat(whatever): add pagination").is_empty());
+
+        let mut configuration = config::Config::default();
+        configuration.commit.scopes = vec!["api".to_string(), "ui".to_string()];
+        assert!(broken("feat(api): add pagination", &configuration).is_empty());
+        assert!(broken("feat(api,ui): add pagination", &configuration).is_empty());
+        assert_eq!(
+            broken("feat(db): add pagination", &configuration),
+            ["scope-enum"]
+        );
+    }
+
+    #[test]
+    fn subject_case_and_full_stop() {
+        assert_eq!(broken_by_default("feat: Add pagination"), ["subject-case"]);
+        assert_eq!(
+            broken_by_default("feat: add pagination."),
+            ["subject-full-stop"]
+        );
+
+        let mut configuration = config::Config::default();
+        configuration.lint.subject_case = SubjectCase::Sentence;
+        assert_eq!(
+            broken("feat: add pagination", &configuration),
+            ["subject-case"]
+        );
+        configuration.lint.subject_case = SubjectCase::Any;
+        assert!(broken("feat: Add pagination", &configuration).is_empty());
+    }
+
+    #[test]
+    fn body_leading_blank_and_line_length() {
+        assert_eq!(
+            broken_by_default("feat: add pagination\nWith a cursor."),
+            ["body-leading-blank"]
+        );
+
+        let long_line = "x".repeat(101);
+        let violations = lint_message(
+            &format!("feat: add pagination\n\nok\n{long_line}"),
+            &config::Config::default(),
+        );
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule, "body-max-line-length");
+        assert_eq!(
+            violations[0].message,
+            "line 4 is 101 characters, the limit is 100"
+        );
+    }
+
+    #[test]
+    fn disabled_rules_are_not_reported() {
+        let mut configuration = config::Config::default();
+        configuration.lint.disabled = vec!["subject-case".to_string(), "type-enum".to_string()];
+
+        assert!(broken("feature: Add pagination", &configuration).is_empty());
+        assert_eq!(
+            broken("feature: Add pagination.", &configuration),
+            ["subject-full-stop"]
+        );
+    }
+
+    #[test]
+    fn git_generated_messages_are_skipped() {
+        assert!(is_ignored("Merge branch 'main' into topic"));
+        assert!(is_ignored("fixup! feat: add pagination"));
+        assert!(!is_ignored("feat: add pagination"));
+    }
+
+    #[test]
+    fn exit_status_follows_the_result() {
+        assert!(run_on_file("valid", "feat: add pagination\n").is_ok());
+        assert!(run_on_file("invalid", "Added pagination.\n").is_err());
+        assert!(run_on_file("fixup", "fixup! Added pagination\n").is_ok());
+        // git aborts empty messages by itself
+        assert!(run_on_file("empty", "# Please enter the commit message\n").is_ok());
+    }
+
+    #[test]
+    fn message_files_drop_comments_and_the_verbose_diff() {
+        let content = format!(
+            "feat: add pagination\n# Please enter the commit message\n{SCISSORS}\ndiff --git a/x b/x\n"
+        );
+        assert!(run_on_file("verbose", &content).is_ok());
+    }
+}
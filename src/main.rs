@@ -1,14 +1,25 @@
 mod ai;
+mod budget;
+mod changelog;
 mod client;
 mod config;
+mod config_cmd;
+mod conventional;
 mod git;
+mod hook;
+mod ignore_cmd;
+mod lint;
+mod pr;
 mod project;
 mod prompts;
-mod system;
+mod reword;
+mod split;
+mod style;
 mod terminal;
 mod update;
 
 use clap::{Parser, Subcommand};
+use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(name = "commayte")]
@@ -23,6 +34,46 @@ struct Cli {
     #[arg(short)]
     push: bool,
 
+    /// Stage all modified and deleted tracked files first, like `git commit -a`
+    #[arg(short, long)]
+    all: bool,
+
+    /// Rewrite the last commit: describe it plus anything staged, then `git commit --amend`
+    #[arg(long)]
+    amend: bool,
+
+    /// Generate a commit body (and footers) in addition to the subject line
+    #[arg(long)]
+    body: bool,
+
+    /// Generate N candidate messages and pick one from a list
+    #[arg(long, value_name = "N", default_value_t = 1, value_parser = clap::value_parser!(u16).range(1..=20))]
+    candidates: u16,
+
+    /// Commit the first valid message without asking
+    #[arg(short, long)]
+    yes: bool,
+
+    /// Print the generated message to stdout instead of committing
+    #[arg(long, visible_alias = "print")]
+    dry_run: bool,
+
+    /// Print the result as JSON (implies --dry-run unless --yes is given)
+    #[arg(long)]
+    json: bool,
+
+    /// Use the message saved by the last failed or cancelled run
+    #[arg(long)]
+    reuse: bool,
+
+    /// Model to use, overriding the configuration files
+    #[arg(long)]
+    model: Option<String>,
+
+    /// LLM provider to use ("ollama" or "openai"), overriding the configuration files
+    #[arg(long)]
+    provider: Option<String>,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -33,6 +84,87 @@ enum Commands {
     Update,
     /// Show version information
     Version,
+    /// Inspect, change and validate settings
+    Config {
+        #[command(subcommand)]
+        action: config_cmd::ConfigAction,
+    },
+    /// Split the staged changes into several commits, one per logical change
+    Split,
+    /// Regenerate the messages of existing commits, e.g. `commayte reword main`
+    Reword {
+        /// Commits to reword: a range like `main..HEAD`, or a ref meaning `<ref>..HEAD`
+        range: String,
+        /// Reword commits that are already on a remote branch
+        #[arg(long)]
+        force: bool,
+    },
+    /// Draft a pull request title and description from the current branch
+    Pr {
+        /// Branch the pull request will be merged into
+        #[arg(long, default_value = "main")]
+        base: String,
+        /// Write the description to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Render a Keep a Changelog section from conventional commits
+    Changelog {
+        /// Start after this ref (default: the latest tag before --to)
+        #[arg(long)]
+        from: Option<String>,
+        /// Last commit to include
+        #[arg(long, default_value = "HEAD")]
+        to: String,
+        /// Release name for the heading (default: the --to tag, or "Unreleased")
+        #[arg(long)]
+        release: Option<String>,
+        /// Also list docs, chore, test and other non user-facing commits
+        #[arg(long)]
+        all: bool,
+        /// Insert the section at the top of a changelog file instead of printing it
+        #[arg(long, value_name = "FILE", num_args = 0..=1, default_missing_value = "CHANGELOG.md")]
+        prepend: Option<PathBuf>,
+    },
+    /// Check commit messages against the conventional format and the [lint] rules
+    Lint {
+        /// Check every commit after this ref instead of only the last one
+        #[arg(long)]
+        from: Option<String>,
+        /// Last commit checked with --from
+        #[arg(long, default_value = "HEAD", requires = "from")]
+        to: String,
+        /// Check a commit message file, e.g. from a commit-msg hook
+        #[arg(long, value_name = "FILE", conflicts_with = "from")]
+        edit: Option<PathBuf>,
+    },
+    /// Show whether a file is left out of the prompt, and which rule decides it
+    ExplainIgnore {
+        /// File to check, relative to the current directory
+        path: PathBuf,
+    },
+    /// Manage the prepare-commit-msg hook that fills messages for plain `git commit`
+    Hook {
+        #[command(subcommand)]
+        action: hook::HookAction,
+    },
+}
+
+impl Cli {
+    /// Configuration values set through command-line flags, the highest-priority layer
+    fn config_overrides(&self) -> Vec<(&'static str, String)> {
+        let mut overrides = Vec::new();
+        if let Some(model) = &self.model {
+            overrides.push(("model", model.clone()));
+        }
+        if let Some(provider) = &self.provider {
+            overrides.push(("provider", provider.clone()));
+        }
+        if self.body {
+            overrides.push(("commit.body", "true".to_string()));
+        }
+        overrides
+    }
 }
 
 fn main() -> anyhow::Result<()> {
@@ -66,9 +198,77 @@ fn main() -> anyhow::Result<()> {
         Some(Commands::Version) => {
             update::show_update_info();
         }
+        Some(Commands::Config { action }) => {
+            config_cmd::run(action, &cli.config_overrides())?;
+        }
+        Some(Commands::Split) => {
+            let configuration = config::load_config(&cli.config_overrides());
+            split::run(&configuration, cli.emoji)?;
+        }
+        Some(Commands::Reword { range, force }) => {
+            let configuration = config::load_config(&cli.config_overrides());
+            reword::run(&configuration, range, cli.emoji, *force)?;
+        }
+        Some(Commands::Pr { base, output }) => {
+            let configuration = config::load_config(&cli.config_overrides());
+            pr::run(&configuration, base, output.as_deref())?;
+        }
+        Some(Commands::Changelog {
+            from,
+            to,
+            release,
+            all,
+            prepend,
+        }) => {
+            let configuration = config::load_config(&cli.config_overrides());
+            changelog::run(
+                &configuration,
+                from.as_deref(),
+                to,
+                release.as_deref(),
+                *all,
+                prepend.as_deref(),
+            )?;
+        }
+        Some(Commands::Lint { from, to, edit }) => {
+            let configuration = config::load_config(&cli.config_overrides());
+            lint::run(&configuration, from.as_deref(), to, edit.as_deref())?;
+        }
+        Some(Commands::ExplainIgnore { path }) => {
+            let configuration = config::load_config(&cli.config_overrides());
+            ignore_cmd::explain(&configuration, path)?;
+        }
+        Some(Commands::Hook { action }) => {
+            hook::run(action, &cli.config_overrides())?;
+        }
         None => {
             // Default behavior - run the commit message generator
-            client::run(cli.emoji, cli.push)?;
+            let configuration = config::load_config(&cli.config_overrides());
+
+            // Never prompt without a terminal: print the message instead
+            let mode = if cli.dry_run {
+                client::Mode::Print
+            } else if cli.yes {
+                client::Mode::Commit
+            } else if cli.json || !terminal::is_interactive() {
+                client::Mode::Print
+            } else {
+                client::Mode::Interactive
+            };
+
+            client::run(
+                &configuration,
+                &client::RunOptions {
+                    use_emoji: cli.emoji,
+                    push: cli.push,
+                    candidates: cli.candidates as usize,
+                    mode,
+                    json: cli.json,
+                    reuse: cli.reuse,
+                    stage_all: cli.all,
+                    amend: cli.amend,
+                },
+            )?;
         }
     }
 
@@ -0,0 +1,137 @@
+use anyhow::{anyhow, Result};
+use colored::*;
+use std::fs;
+use std::io::IsTerminal;
+use std::path::Path;
+
+use crate::ai;
+use crate::budget;
+use crate::config;
+use crate::git;
+use crate::project;
+use crate::prompts;
+use crate::terminal;
+
+/// Most commit messages sent to the model, the diff already tells the rest
+const MAX_COMMITS: usize = 50;
+
+/// Formats the branch's commits as a markdown list, bodies indented below subjects
+fn format_commits(commits: &[git::CommitInfo]) -> String {
+    let mut formatted = String::new();
+    for commit in commits.iter().take(MAX_COMMITS) {
+        let mut lines = commit.message.lines();
+        formatted.push_str(&format!("- {}\n", lines.next().unwrap_or("")));
+        for line in lines.filter(|line| !line.trim().is_empty()) {
+            formatted.push_str(&format!("  {line}\n"));
+        }
+    }
+    if commits.len() > MAX_COMMITS {
+        formatted.push_str(&format!(
+            "- ... and {} more commits\n",
+            commits.len() - MAX_COMMITS
+        ));
+    }
+    formatted
+}
+
+/// Splits the model's answer into a title and a markdown body
+fn parse_response(response: &str) -> Result<(String, String)> {
+    let mut text = response.trim();
+    // Some models wrap the whole answer in a code fence
+    if text.starts_with("```") && text.ends_with("```") && text.len() > 6 {
+        text = text[3..text.len() - 3]
+            .split_once('\n')
+            .map(|(_, inner)| inner)
+            .unwrap_or("")
+            .trim();
+    }
+
+    let (first_line, rest) = text.split_once('\n').unwrap_or((text, ""));
+    let mut title = first_line.trim().trim_start_matches('#').trim();
+    for label in ["Title:", "title:", "**Title:**"] {
+        if let Some(stripped) = title.strip_prefix(label) {
+            title = stripped.trim();
+        }
+    }
+    let title = title.trim_matches(|c| matches!(c, '"' | '\'' | '`' | '*'));
+    if title.is_empty() {
+        return Err(anyhow!("The model did not return a pull request title"));
+    }
+
+    let mut body = rest.trim();
+    for label in ["Description:", "description:", "**Description:**"] {
+        if let Some(stripped) = body.strip_prefix(label) {
+            body = stripped.trim_start();
+        }
+    }
+
+    Ok((title.to_string(), body.to_string()))
+}
+
+pub fn run(configuration: &config::Config, base: &str, output: Option<&Path>) -> Result<()> {
+    let backend = git::open_backend(&configuration.git)?;
+
+    let commits: Vec<git::CommitInfo> = backend
+        .commits_in_range(&format!("{base}..HEAD"))?
+        .into_iter()
+        .filter(|commit| !commit.is_merge)
+        .collect();
+    let full_diff = backend.branch_diff(base)?;
+    if commits.is_empty() || full_diff.trim().is_empty() {
+        eprintln!(
+            "{}",
+            format!("⚠️  No changes between {base} and HEAD.").yellow()
+        );
+        return Ok(());
+    }
+
+    let commit_list = format_commits(&commits);
+    let project_context = project::get_project_context();
+    let prompt = |diff: &str| {
+        prompts::generate_pr_prompt(
+            diff,
+            &commit_list,
+            &project_context,
+            base,
+            &configuration.commit,
+        )
+    };
+
+    // The commit list can be long, the diff only gets what it leaves of the window
+    let diff_budget = budget::diff_budget(budget::context_window(configuration), &prompt(""));
+    let diff = git::filter_diff(
+        &full_diff,
+        diff_budget,
+        &git::IgnoreRules::load(&configuration.ignore),
+    );
+    let prompt = prompt(&diff);
+
+    // Keep stdout clean when the description is piped somewhere
+    let mut spinner = if std::io::stdout().is_terminal() {
+        Some(terminal::show_spinner(
+            "Generating pull request description...",
+        ))
+    } else {
+        eprintln!("{}", "🤖 Generating pull request description...".dimmed());
+        None
+    };
+    let response = ai::create_provider(configuration).generate(&prompt, &ai::Sampling::default());
+    if let Some(spinner) = spinner.as_mut() {
+        spinner.stop();
+        println!();
+    }
+    let (title, body) = parse_response(&response?)?;
+
+    let description = format!("{title}\n\n{body}\n");
+    match output {
+        Some(path) => {
+            fs::write(path, &description)?;
+            println!(
+                "✅ Pull request description written to {}",
+                path.display().to_string().bold()
+            );
+        }
+        None => print!("{description}"),
+    }
+    Ok(())
+}
@@ -1,22 +1,76 @@
-pub fn generate_commit_prompt(diff: &str, project_context: &str, use_emoji: bool) -> String {
+use crate::config;
+
+/// Extra instructions asking for a body and footers after the subject line
+const BODY_INSTRUCTIONS: &str = "Message structure:\n\
+    - First line: the subject, in the format above\n\
+    - Then a blank line and a short body (1-3 sentences, wrapped at 72 characters) \
+    explaining WHY the change was made, not how\n\
+    - If the change breaks backward compatibility, end with a blank line and a footer \
+    'BREAKING CHANGE: <what breaks>'\n\
+    - Add a 'Refs: <id>' footer only if an issue id appears in the diff\n\n";
+
+/// Lists the configured commit types, one `- name: description` line each
+fn format_type_list(commit_config: &config::CommitConfig) -> String {
+    commit_config
+        .types
+        .iter()
+        .map(|commit_type| {
+            if commit_type.description.is_empty() {
+                format!("- {}\n", commit_type.name)
+            } else {
+                format!("- {}: {}\n", commit_type.name, commit_type.description)
+            }
+        })
+        .collect()
+}
+
+/// Tells the model which scopes it may use
+fn format_scope_instruction(commit_config: &config::CommitConfig) -> String {
+    if commit_config.scopes.is_empty() {
+        "Determine scope from file path (e.g., client, server, ui)".to_string()
+    } else {
+        format!(
+            "Use one of these scopes, or omit the scope if none fits: {}",
+            commit_config.scopes.join(", ")
+        )
+    }
+}
+
+/// `style_context` describes the repository's own commits (see `style::get_style_context`)
+/// and may be empty
+pub fn generate_commit_prompt(
+    diff: &str,
+    project_context: &str,
+    style_context: &str,
+    use_emoji: bool,
+    commit_config: &config::CommitConfig,
+) -> String {
+    let style_section = if style_context.is_empty() {
+        String::new()
+    } else {
+        format!("{style_context}\n")
+    };
+    let body_instructions = if commit_config.body {
+        BODY_INSTRUCTIONS
+    } else {
+        ""
+    };
+    let type_list = format_type_list(commit_config);
+    let scope_instruction = format_scope_instruction(commit_config);
+
     if use_emoji {
         format!(
             "Analyze the git diff below and generate a conventional commit message.\n\n\
             Project context:\n{project_context}\n\n\
+            {style_section}\
             Instructions:\n\
             1. Look at each file name, added lines (+), and removed lines (-)\n\
             2. Determine the type based on changes:\n\
-               - feat: new features\n\
-               - fix: bug fixes\n\
-               - chore: maintenance/config\n\
-               - docs: documentation\n\
-               - style: formatting\n\
-               - refactor: code restructuring\n\
-               - test: adding/updating tests\n\
-               - perf: performance improvements\n\
+            {type_list}\
             3. Format: type description\n\
             4. Keep description concise\n\
             5. Return ONLY the commit message\n\n\
+            {body_instructions}\
             RESPECT CONVENTIONAL COMMIT SPECIFICATION.\n\n\
             RETURN ONLY THE COMMIT MESSAGE.\n\n\
             RESPECT CONVENTIONAL COMMIT SPECIFICATION.\n\n\
@@ -27,22 +81,17 @@ pub fn generate_commit_prompt(diff: &str, project_context: &str, use_emoji: bool
         format!(
             "Analyze the git diff below and generate a conventional commit message.\n\n\
             Project context:\n{project_context}\n\n\
+            {style_section}\
             Instructions:\n\
             1. Look at each file name, added lines (+), and removed lines (-)\n\
             2. Determine the type based on changes:\n\
-               - feat: new features\n\
-               - fix: bug fixes\n\
-               - chore: maintenance/config\n\
-               - docs: documentation\n\
-               - style: formatting\n\
-               - refactor: code restructuring\n\
-               - test: adding/updating tests\n\
-               - perf: performance improvements\n\
-            3. Determine scope from file path (e.g., client, server, ui)\n\
+            {type_list}\
+            3. {scope_instruction}\n\
             4. Write description of what changed\n\
             5. Format: type(scope): description\n\
             6. Keep description concise\n\
             7. Return ONLY the commit message\n\n\
+            {body_instructions}\
             RESPECT CONVENTIONAL COMMIT SPECIFICATION.\n\n\
             RETURN ONLY THE COMMIT MESSAGE.\n\n\
             RESPECT CONVENTIONAL COMMIT SPECIFICATION.\n\n\
@@ -51,3 +100,60 @@ pub fn generate_commit_prompt(diff: &str, project_context: &str, use_emoji: bool
         )
     }
 }
+
+/// Asks the model to group changed files into independent commits.
+/// `files` lists one `path (+added -removed)` entry per line.
+pub fn generate_split_prompt(files: &str, suggested_groups: &str, max_groups: usize) -> String {
+    format!(
+        "The files below are staged for a single git commit. Group them into logical, \
+        independent changes so each group can become its own conventional commit.\n\n\
+        Instructions:\n\
+        1. Put files that implement the same feature or fix in the same group\n\
+        2. Keep tests and docs with the code they belong to\n\
+        3. Use at most {max_groups} groups, fewer if the changes are related\n\
+        4. Every file must appear in exactly one group\n\
+        5. Answer with one line per group: the group number, a colon, then the file paths separated by commas\n\n\
+        Example answer:\n\
+        1: src/api.rs, tests/api.rs\n\
+        2: README.md\n\n\
+        Grouping by directory would give:\n{suggested_groups}\n\n\
+        Files:\n{files}\n\n\
+        RETURN ONLY THE GROUPS.\n\n\
+        Groups:"
+    )
+}
+
+/// Asks for a pull request title and markdown description covering a whole branch.
+/// `commits` lists the branch's commit messages, oldest first.
+pub fn generate_pr_prompt(
+    diff: &str,
+    commits: &str,
+    project_context: &str,
+    base: &str,
+    commit_config: &config::CommitConfig,
+) -> String {
+    let type_list = format_type_list(commit_config);
+
+    format!(
+        "Write a pull request title and description for the branch below, \
+        which is about to be merged into {base}.\n\n\
+        Project context:\n{project_context}\n\n\
+        Instructions:\n\
+        1. First line: the title, as a conventional commit header 'type(scope): description' \
+        under 72 characters, using one of these types:\n\
+        {type_list}\
+        2. Then a blank line and a markdown description with exactly these sections:\n\
+        ## Summary\n\
+        1-3 sentences on what the branch does and why\n\
+        ## Changes\n\
+        A bullet list of the notable changes\n\
+        ## Testing\n\
+        How the changes can be verified, based on the tests and code in the diff\n\
+        3. Describe the overall result, not each commit one by one\n\
+        4. Do not invent issues, links or test results that are not in the commits or diff\n\n\
+        Commits:\n{commits}\n\n\
+        Git diff:\n{diff}\n\n\
+        RETURN ONLY THE TITLE AND THE DESCRIPTION.\n\n\
+        Pull request:"
+    )
+}
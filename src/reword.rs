@@ -0,0 +1,279 @@
+use anyhow::{anyhow, Result};
+use colored::*;
+
+use crate::budget;
+use crate::client;
+use crate::config;
+use crate::git;
+use crate::project;
+use crate::prompts;
+use crate::style;
+use crate::terminal;
+
+/// A commit of the range and the message it will get
+struct Reword {
+    commit: git::CommitInfo,
+    message: String,
+}
+
+impl Reword {
+    fn changed(&self) -> bool {
+        self.message.trim() != self.commit.message.trim()
+    }
+}
+
+/// A single ref means "everything after it", like `git rebase -i <ref>`.
+/// Symmetric ranges (`a...b`) are refused, they are not one line of history.
+fn normalize_range(range: &str) -> Result<String> {
+    if range.contains("...") {
+        Err(anyhow!(
+            "Symmetric ranges like {range} cannot be reworded, use <base>..HEAD"
+        ))
+    } else if range.contains("..") {
+        Ok(range.to_string())
+    } else {
+        Ok(format!("{range}..HEAD"))
+    }
+}
+
+fn short_id(id: &str) -> &str {
+    &id[..id.len().min(7)]
+}
+
+fn subject(message: &str) -> &str {
+    message.lines().next().unwrap_or("")
+}
+
+fn generate_message(
+    backend: &dyn git::GitBackend,
+    commit: &git::CommitInfo,
+    configuration: &config::Config,
+    use_emoji: bool,
+    project_context: &str,
+    context_window: usize,
+) -> Result<String> {
+    let full_diff = backend.commit_diff(&commit.id)?;
+    if full_diff.trim().is_empty() {
+        // An empty commit, nothing the model could describe
+        return Ok(commit.message.clone());
+    }
+
+    let style_context = style::get_style_context(backend, &full_diff, &configuration.commit);
+    let prompt = |diff: &str| {
+        prompts::generate_commit_prompt(
+            diff,
+            project_context,
+            &style_context,
+            use_emoji,
+            &configuration.commit,
+        )
+    };
+    let diff = git::filter_diff(
+        &full_diff,
+        budget::diff_budget(context_window, &prompt("")),
+        &git::IgnoreRules::load(&configuration.ignore),
+    );
+    let prompt = prompt(&diff);
+    match client::generate_valid_message(&prompt, configuration, use_emoji)? {
+        Some(message) => Ok(message),
+        None => {
+            eprintln!(
+                "{}",
+                "⚠️  No valid message was generated, keeping the current one.".yellow()
+            );
+            Ok(commit.message.clone())
+        }
+    }
+}
+
+pub fn run(
+    configuration: &config::Config,
+    range: &str,
+    use_emoji: bool,
+    force: bool,
+) -> Result<()> {
+    if !terminal::is_interactive() {
+        return Err(anyhow!("'commayte reword' needs an interactive terminal"));
+    }
+
+    let backend = git::open_backend(&configuration.git)?;
+    let range = normalize_range(range)?;
+    let (_, tip) = range.split_once("..").unwrap_or(("", "HEAD"));
+    if !tip.is_empty() && tip != "HEAD" {
+        return Err(anyhow!(
+            "Only commits up to HEAD can be reworded, check out {tip} first"
+        ));
+    }
+
+    let commits = backend.commits_in_range(&range)?;
+    if commits.is_empty() {
+        println!("{}", format!("⚠️  No commits in {range}.").yellow());
+        return Ok(());
+    }
+    if let Some(merge) = commits.iter().find(|commit| commit.is_merge) {
+        return Err(anyhow!(
+            "{range} contains the merge commit {}, only linear history can be reworded",
+            short_id(&merge.id)
+        ));
+    }
+
+    if !force {
+        for commit in &commits {
+            if backend.is_pushed(&commit.id)? {
+                return Err(anyhow!(
+                    "{} {} is already on a remote branch, rewording it rewrites shared history\nUse --force to reword it anyway",
+                    short_id(&commit.id),
+                    subject(&commit.message)
+                ));
+            }
+        }
+    }
+
+    let project_context = project::get_project_context();
+    let context_window = budget::context_window(configuration);
+
+    let total = commits.len();
+    let mut rewords: Vec<Reword> = Vec::new();
+    for (index, commit) in commits.into_iter().enumerate() {
+        let mut message = {
+            let mut sp = terminal::show_spinner("Generating commit message...");
+            let message = generate_message(
+                backend.as_ref(),
+                &commit,
+                configuration,
+                use_emoji,
+                &project_context,
+                context_window,
+            );
+            sp.stop();
+            message?
+        };
+
+        loop {
+            terminal::clear_terminal();
+            terminal::print_header(
+                &format!("Commit {}/{total} ({})", index + 1, short_id(&commit.id)),
+                Some(console::Color::Yellow),
+            );
+            println!(
+                "{} {}",
+                "Old:".bold().red(),
+                subject(&commit.message).dimmed()
+            );
+            println!();
+            client::print_commit_message("New:", &message);
+            println!();
+
+            let options = vec![
+                "✅ Use new message",
+                "✏️ Edit",
+                "🔄 Regenerate",
+                "⏭️ Keep old message",
+                "❌ Abort",
+            ];
+            let selection =
+                terminal::show_selection_menu(options.clone(), "What would you like to do?")?;
+            match options[selection] {
+                "✅ Use new message" => break,
+                "✏️ Edit" => {
+                    match terminal::edit_commit_message(&message, configuration.commit.body) {
+                        Ok(edited) => message = edited,
+                        Err(e) if e.to_string().contains("Editing cancelled by user") => {}
+                        Err(e) => return Err(e),
+                    }
+                }
+                "🔄 Regenerate" => {
+                    let mut sp = terminal::show_spinner("Generating commit message...");
+                    let regenerated = generate_message(
+                        backend.as_ref(),
+                        &commit,
+                        configuration,
+                        use_emoji,
+                        &project_context,
+                        context_window,
+                    );
+                    sp.stop();
+                    message = regenerated?;
+                }
+                "⏭️ Keep old message" => {
+                    message = commit.message.clone();
+                    break;
+                }
+                _ => {
+                    terminal::clear_terminal();
+                    println!("{}", "❌ Cancelled by user, no commit was changed".red());
+                    return Ok(());
+                }
+            }
+        }
+
+        rewords.push(Reword { commit, message });
+    }
+
+    // Commits before the first new message stay as they are
+    let Some(first_change) = rewords.iter().position(Reword::changed) else {
+        terminal::clear_terminal();
+        println!("{}", "⚠️  All messages kept, nothing to rewrite.".yellow());
+        return Ok(());
+    };
+
+    terminal::clear_terminal();
+    println!("{}", "Commits to rewrite:".bold());
+    println!();
+    for reword in &rewords[first_change..] {
+        if reword.changed() {
+            println!(
+                "{} {} {}",
+                short_id(&reword.commit.id).yellow(),
+                subject(&reword.commit.message).dimmed().strikethrough(),
+                subject(&reword.message).white()
+            );
+        } else {
+            println!(
+                "{} {}",
+                short_id(&reword.commit.id).yellow(),
+                subject(&reword.message).dimmed()
+            );
+        }
+    }
+    println!();
+
+    let options = vec!["✅ Rewrite commits", "❌ Cancel"];
+    if terminal::show_selection_menu(options, "Rewrite these commits?")? != 0 {
+        terminal::clear_terminal();
+        println!("{}", "❌ Cancelled by user, no commit was changed".red());
+        return Ok(());
+    }
+
+    let rewrites: Vec<(String, String)> = rewords[first_change..]
+        .iter()
+        .map(|reword| (reword.commit.id.clone(), reword.message.clone()))
+        .collect();
+    backend.reword(&rewrites)?;
+
+    println!(
+        "{}",
+        format!("✅ Reworded {} commit(s)!", rewrites.len())
+            .bold()
+            .green()
+    );
+    if force {
+        println!(
+            "{}",
+            "💡 Commits that were already pushed need `git push --force-with-lease`".dimmed()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_ranges() {
+        assert_eq!(normalize_range("main").unwrap(), "main..HEAD");
+        assert_eq!(normalize_range("HEAD~3..HEAD").unwrap(), "HEAD~3..HEAD");
+        assert!(normalize_range("main...HEAD").is_err());
+    }
+}
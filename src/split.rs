@@ -0,0 +1,391 @@
+use anyhow::{anyhow, Result};
+use colored::*;
+use std::collections::HashSet;
+use std::fs;
+
+use crate::ai;
+use crate::budget;
+use crate::client;
+use crate::config;
+use crate::git;
+use crate::project;
+use crate::prompts;
+use crate::style;
+use crate::terminal;
+
+/// Most commits proposed for one staged change
+const MAX_GROUPS: usize = 6;
+
+/// Copy of the full staged patch, kept in the git directory while commits are created
+const BACKUP_FILE: &str = "COMMAYTE_SPLIT.patch";
+
+/// Files committed together, with their message
+struct Group {
+    files: Vec<String>,
+    message: String,
+}
+
+/// Everything needed to generate messages for groups of the staged files
+struct SplitContext<'a> {
+    configuration: &'a config::Config,
+    use_emoji: bool,
+    files: Vec<git::FileDiff>,
+    project_context: String,
+    style_context: String,
+    /// Tokens each group's diff may use
+    diff_budget: usize,
+    ignore_rules: git::IgnoreRules,
+}
+
+impl SplitContext<'_> {
+    fn patch_of(&self, path: &str) -> &str {
+        self.files
+            .iter()
+            .find(|file| file.path == path)
+            .map(|file| file.patch.as_str())
+            .unwrap_or("")
+    }
+
+    /// Patch staging exactly the files of `group`
+    fn group_patch(&self, group: &Group) -> String {
+        group.files.iter().map(|path| self.patch_of(path)).collect()
+    }
+
+    fn generate_message(&self, files: &[String]) -> Result<String> {
+        let readable: String = files
+            .iter()
+            .map(|path| readable_patch(self.patch_of(path)))
+            .collect();
+        let diff = git::filter_diff(&readable, self.diff_budget, &self.ignore_rules);
+
+        let prompt = prompts::generate_commit_prompt(
+            &diff,
+            &self.project_context,
+            &self.style_context,
+            self.use_emoji,
+            &self.configuration.commit,
+        );
+        client::generate_valid_message(&prompt, self.configuration, self.use_emoji)?
+            .ok_or_else(|| anyhow!("No valid commit message for {}", files.join(", ")))
+    }
+}
+
+/// Replaces base85 binary data with a short note, it means nothing to the model
+fn readable_patch(patch: &str) -> String {
+    match patch.find("GIT binary patch") {
+        Some(start) => format!("{}Binary file changed\n", &patch[..start]),
+        None => patch.to_string(),
+    }
+}
+
+/// Directory-based key used to group files without the model
+fn path_group_key(path: &str) -> String {
+    let lower = path.to_lowercase();
+    if lower.starts_with("docs/") || lower.ends_with(".md") || lower.ends_with(".rst") {
+        return "docs".to_string();
+    }
+
+    let parts: Vec<&str> = path.split('/').collect();
+    match parts.len() {
+        1 => ".".to_string(),
+        2 => parts[0].to_string(),
+        _ => format!("{}/{}", parts[0], parts[1]),
+    }
+}
+
+/// Groups files by directory, keeping the order of the diff
+fn group_by_path(paths: &[String]) -> Vec<Vec<String>> {
+    let mut groups: Vec<(String, Vec<String>)> = Vec::new();
+    for path in paths {
+        let key = path_group_key(path);
+        match groups.iter_mut().find(|(group_key, _)| *group_key == key) {
+            Some((_, files)) => files.push(path.clone()),
+            None => groups.push((key, vec![path.clone()])),
+        }
+    }
+    groups.into_iter().map(|(_, files)| files).collect()
+}
+
+/// Reads the model's `N: path, path` lines. Unknown paths are dropped and files the
+/// model forgot are grouped by directory, so every file ends up in exactly one group.
+fn parse_groups(response: &str, paths: &[String]) -> Vec<Vec<String>> {
+    let mut assigned: HashSet<&str> = HashSet::new();
+    let mut groups = Vec::new();
+
+    for line in response.lines() {
+        let Some((_, list)) = line.split_once(':') else {
+            continue;
+        };
+
+        let mut group = Vec::new();
+        for item in list.split(',') {
+            let item = item.trim().trim_matches(['`', '"', '\'']);
+            if let Some(path) = paths.iter().find(|path| path.as_str() == item) {
+                if assigned.insert(path) {
+                    group.push(path.clone());
+                }
+            }
+        }
+        if !group.is_empty() {
+            groups.push(group);
+        }
+    }
+
+    let forgotten: Vec<String> = paths
+        .iter()
+        .filter(|path| !assigned.contains(path.as_str()))
+        .cloned()
+        .collect();
+    groups.extend(group_by_path(&forgotten));
+    groups
+}
+
+/// Asks the model for a grouping, falling back to directories when it fails
+fn propose_groups(context: &SplitContext) -> Vec<Vec<String>> {
+    let paths: Vec<String> = context.files.iter().map(|file| file.path.clone()).collect();
+    let by_path = group_by_path(&paths);
+    if paths.len() < 2 {
+        return by_path;
+    }
+
+    let file_list: String = context
+        .files
+        .iter()
+        .map(|file| {
+            let (added, removed) = git::line_counts(&file.patch);
+            format!("{} (+{added} -{removed})\n", file.path)
+        })
+        .collect();
+    let suggested: String = by_path
+        .iter()
+        .enumerate()
+        .map(|(index, files)| format!("{}: {}\n", index + 1, files.join(", ")))
+        .collect();
+
+    let prompt = prompts::generate_split_prompt(&file_list, &suggested, MAX_GROUPS);
+    let provider = ai::create_provider(context.configuration);
+    match provider.generate(&prompt, &ai::Sampling::default()) {
+        Ok(response) => parse_groups(&response, &paths),
+        Err(e) => {
+            println!(
+                "{} {}",
+                "⚠️  Could not group files with the model, grouping by directory:".yellow(),
+                e
+            );
+            by_path
+        }
+    }
+}
+
+fn subject(message: &str) -> &str {
+    message.lines().next().unwrap_or("")
+}
+
+fn print_plan(groups: &[Group]) {
+    println!("{}", "Proposed commits:".bold());
+    println!();
+    for (index, group) in groups.iter().enumerate() {
+        println!(
+            "{} {}",
+            format!("{}.", index + 1).bold(),
+            subject(&group.message).white()
+        );
+        for file in &group.files {
+            println!("     {}", file.dimmed());
+        }
+    }
+    println!();
+}
+
+/// Lets the user pick a group by its subject, optionally leaving one out
+fn pick_group(groups: &[Group], prompt: &str, exclude: Option<usize>) -> Result<usize> {
+    let indices: Vec<usize> = (0..groups.len()).filter(|i| Some(*i) != exclude).collect();
+    let labels: Vec<String> = indices
+        .iter()
+        .map(|i| format!("{}. {}", i + 1, subject(&groups[*i].message)))
+        .collect();
+    let selection =
+        terminal::show_selection_menu(labels.iter().map(String::as_str).collect(), prompt)?;
+    Ok(indices[selection])
+}
+
+/// Creates one commit per group. On failure the remaining groups are staged again,
+/// so the index ends up as it was minus the commits already made.
+fn create_commits(
+    backend: &dyn git::GitBackend,
+    context: &SplitContext,
+    groups: &[Group],
+    full_patch: &str,
+) -> Result<()> {
+    let backup = git::git_dir_path(BACKUP_FILE)?;
+    fs::write(&backup, full_patch)?;
+
+    backend.reset_index()?;
+    for (index, group) in groups.iter().enumerate() {
+        let result = backend
+            .apply_to_index(&context.group_patch(group))
+            .and_then(|_| backend.commit(&group.message));
+
+        if let Err(e) = result {
+            let restore_from = if backend.staged_diff()?.trim().is_empty() {
+                index
+            } else {
+                index + 1
+            };
+            for rest in &groups[restore_from..] {
+                if backend.apply_to_index(&context.group_patch(rest)).is_err() {
+                    return Err(anyhow!(
+                        "{e}\nThe staged changes could not be restored, they are saved in {}",
+                        backup.display()
+                    ));
+                }
+            }
+            let _ = git::save_message(&group.message);
+            let _ = fs::remove_file(&backup);
+            return Err(anyhow!(
+                "Commit {}/{} failed, the remaining changes are staged again: {e}",
+                index + 1,
+                groups.len()
+            ));
+        }
+
+        println!(
+            "✅ {}/{} {}",
+            index + 1,
+            groups.len(),
+            subject(&group.message).white()
+        );
+    }
+
+    let _ = fs::remove_file(&backup);
+    Ok(())
+}
+
+pub fn run(configuration: &config::Config, use_emoji: bool) -> Result<()> {
+    if !terminal::is_interactive() {
+        return Err(anyhow!("'commayte split' needs an interactive terminal"));
+    }
+
+    let backend = git::open_backend(&configuration.git)?;
+    let full_patch = backend.staged_patch()?;
+    if full_patch.trim().is_empty() {
+        println!("{}", "⚠️  No changes to commit.".yellow());
+        return Ok(());
+    }
+
+    let project_context = project::get_project_context();
+    let style_context =
+        style::get_style_context(backend.as_ref(), &full_patch, &configuration.commit);
+    let diff_budget = budget::diff_budget(
+        budget::context_window(configuration),
+        &prompts::generate_commit_prompt(
+            "",
+            &project_context,
+            &style_context,
+            use_emoji,
+            &configuration.commit,
+        ),
+    );
+    let context = SplitContext {
+        configuration,
+        use_emoji,
+        files: git::split_diff_by_file(&full_patch),
+        project_context,
+        style_context,
+        diff_budget,
+        ignore_rules: git::IgnoreRules::load(&configuration.ignore),
+    };
+
+    terminal::clear_terminal();
+    terminal::print_header(
+        &format!("Using model: {}", configuration.model),
+        Some(console::Color::Yellow),
+    );
+
+    let mut sp = terminal::show_spinner("Grouping files...");
+    let file_groups = propose_groups(&context);
+    sp.stop();
+    println!();
+
+    let mut groups = Vec::new();
+    for (index, files) in file_groups.into_iter().enumerate() {
+        let mut sp = terminal::show_spinner(&format!("Generating message {}...", index + 1));
+        let message = context.generate_message(&files)?;
+        sp.stop();
+        println!();
+        groups.push(Group { files, message });
+    }
+
+    loop {
+        terminal::clear_terminal();
+        print_plan(&groups);
+
+        let create = format!("✅ Create {} commit(s)", groups.len());
+        let mut options = vec![
+            create.as_str(),
+            "✏️ Edit a message",
+            "🔄 Regenerate a message",
+        ];
+        if groups.len() > 1 {
+            options.push("🔗 Merge two groups");
+            options.push("↕️ Move a group");
+        }
+        options.push("❌ Cancel");
+
+        let selection =
+            terminal::show_selection_menu(options.clone(), "What would you like to do?")?;
+        match options[selection] {
+            "✏️ Edit a message" => {
+                let index = pick_group(&groups, "Which message?", None)?;
+                match terminal::edit_commit_message(
+                    &groups[index].message,
+                    configuration.commit.body,
+                ) {
+                    Ok(message) => groups[index].message = message,
+                    Err(e) if e.to_string().contains("Editing cancelled by user") => {}
+                    Err(e) => return Err(e),
+                }
+            }
+            "🔄 Regenerate a message" => {
+                let index = pick_group(&groups, "Which message?", None)?;
+                let mut sp = terminal::show_spinner("Generating commit message...");
+                groups[index].message = context.generate_message(&groups[index].files)?;
+                sp.stop();
+            }
+            "🔗 Merge two groups" => {
+                let target = pick_group(&groups, "Merge into", None)?;
+                let source = pick_group(&groups, "Merge which group?", Some(target))?;
+                let merged = groups.remove(source);
+                let target = if source < target { target - 1 } else { target };
+                groups[target].files.extend(merged.files);
+
+                let mut sp = terminal::show_spinner("Generating commit message...");
+                groups[target].message = context.generate_message(&groups[target].files)?;
+                sp.stop();
+            }
+            "↕️ Move a group" => {
+                let index = pick_group(&groups, "Move which group?", None)?;
+                let positions: Vec<String> = (1..=groups.len())
+                    .map(|position| position.to_string())
+                    .collect();
+                let position = terminal::show_selection_menu(
+                    positions.iter().map(String::as_str).collect(),
+                    "To position",
+                )?;
+                let group = groups.remove(index);
+                groups.insert(position, group);
+            }
+            "❌ Cancel" => {
+                terminal::clear_terminal();
+                println!("{}", "❌ Cancelled by user".red());
+                return Ok(());
+            }
+            _ => {
+                terminal::clear_terminal();
+                create_commits(backend.as_ref(), &context, &groups, &full_patch)?;
+                println!("{}", "✅ All commits created!".bold().green());
+                return Ok(());
+            }
+        }
+    }
+}
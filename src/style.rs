@@ -0,0 +1,155 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::ai;
+use crate::config;
+use crate::conventional::ConventionalCommit;
+use crate::git;
+
+/// Commits read to infer the repository's scopes and casing
+const HISTORY_DEPTH: usize = 200;
+
+/// Most scopes listed in the prompt
+const MAX_SCOPES: usize = 12;
+
+/// Staged paths used to look up related commits, more would make `git log` slow
+const MAX_PATHS: usize = 50;
+
+/// Longest full message used as an example when bodies are enabled
+const MAX_EXAMPLE_LENGTH: usize = 500;
+
+/// Share of subjects that must agree before their casing counts as a convention
+const CASING_THRESHOLD: f64 = 0.8;
+
+/// A past commit that follows the configured conventions
+struct Sample {
+    text: String,
+    commit: ConventionalCommit,
+}
+
+/// Keeps the conventional commits with an allowed type, without their gitmoji
+fn samples(commits: Vec<git::CommitInfo>, commit_config: &config::CommitConfig) -> Vec<Sample> {
+    let types = commit_config.type_names();
+    commits
+        .into_iter()
+        .filter(|commit| !commit.is_merge)
+        .filter_map(|commit| {
+            let (_, message) = ai::split_leading_emoji(&commit.message);
+            let parsed = ConventionalCommit::parse_header(message.lines().next()?).ok()?;
+            parsed.check_type(&types).ok()?;
+
+            let text = if commit_config.body && message.len() <= MAX_EXAMPLE_LENGTH {
+                message.trim().to_string()
+            } else {
+                parsed.header()
+            };
+            Some(Sample {
+                text,
+                commit: parsed,
+            })
+        })
+        .collect()
+}
+
+/// Scopes of `samples`, most used first; scopes used only once are left out
+fn frequent_scopes(samples: &[Sample]) -> Vec<String> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for sample in samples {
+        if let Some(scope) = &sample.commit.scope {
+            *counts.entry(scope.as_str()).or_default() += 1;
+        }
+    }
+
+    let mut scopes: Vec<(&str, usize)> = counts.into_iter().filter(|(_, n)| *n > 1).collect();
+    scopes.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    scopes
+        .into_iter()
+        .take(MAX_SCOPES)
+        .map(|(scope, _)| scope.to_string())
+        .collect()
+}
+
+/// Describes how descriptions start, when the history agrees on it
+fn casing_hint(samples: &[Sample]) -> Option<&'static str> {
+    let firsts: Vec<char> = samples
+        .iter()
+        .filter_map(|sample| sample.commit.description.chars().next())
+        .filter(|c| c.is_alphabetic())
+        .collect();
+    if firsts.len() < 5 {
+        return None;
+    }
+
+    let lowercase = firsts.iter().filter(|c| c.is_lowercase()).count() as f64;
+    let share = lowercase / firsts.len() as f64;
+    if share >= CASING_THRESHOLD {
+        Some("Descriptions start with a lowercase letter.")
+    } else if share <= 1.0 - CASING_THRESHOLD {
+        Some("Descriptions start with an uppercase letter.")
+    } else {
+        None
+    }
+}
+
+/// Describes how this repository writes commits: a few past messages, preferably for
+/// the same files as `diff`, plus the scopes and casing its history uses.
+/// Returns an empty string when there is no usable history.
+pub fn get_style_context(
+    backend: &dyn git::GitBackend,
+    diff: &str,
+    commit_config: &config::CommitConfig,
+) -> String {
+    if commit_config.examples == 0 {
+        return String::new();
+    }
+
+    // History only improves the prompt, so a failure here must not block the commit
+    let recent = samples(
+        backend.history(HISTORY_DEPTH).unwrap_or_default(),
+        commit_config,
+    );
+    let paths: Vec<String> = git::split_diff_by_file(diff)
+        .into_iter()
+        .map(|file| file.path)
+        .take(MAX_PATHS)
+        .collect();
+    let related = samples(
+        backend
+            .history_for_paths(&paths, commit_config.examples * 4)
+            .unwrap_or_default(),
+        commit_config,
+    );
+
+    let mut seen = HashSet::new();
+    let examples: Vec<&str> = related
+        .iter()
+        .chain(recent.iter())
+        .map(|sample| sample.text.as_str())
+        .filter(|text| seen.insert(*text))
+        .take(commit_config.examples)
+        .collect();
+    if examples.is_empty() {
+        return String::new();
+    }
+
+    let mut context = String::from("Recent commits in this repository, follow their style:\n");
+    for example in examples {
+        context.push_str(&format!("- {}\n", example.replace('\n', "\n  ")));
+    }
+
+    // Configured scopes are already listed as the only allowed ones
+    if commit_config.scopes.is_empty() {
+        let scopes = frequent_scopes(&recent);
+        if !scopes.is_empty() {
+            context.push_str(&format!(
+                "Scopes used in this repository, most common first: {}\n",
+                scopes.join(", ")
+            ));
+        }
+    }
+    if let Some(hint) = casing_hint(&recent) {
+        context.push_str(hint);
+        context.push('\n');
+    }
+
+    context
+}
@@ -7,12 +7,19 @@ use crossterm::{
     style::Print,
     terminal::{self, Clear, ClearType},
 };
-use dialoguer::{theme::ColorfulTheme, Select};
+use dialoguer::{theme::ColorfulTheme, Editor, MultiSelect, Select};
 use spinners::{Spinner, Spinners};
-use std::io::{self, stdout, Write};
+use std::io::{self, stdout, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Once;
 use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 
+/// Returns true when both stdin and stdout are attached to a terminal, so menus can be shown
+pub fn is_interactive() -> bool {
+    io::stdin().is_terminal() && io::stdout().is_terminal()
+}
+
 /// Clears the terminal screen
 pub fn clear_terminal() {
     io::stdout().flush().unwrap();
@@ -37,6 +44,8 @@ pub fn print_header(title: &str, color: Option<console::Color>) {
     }
 }
 
+// The key arms keep their bounds checks as plain `if`s; newer clippy wants match guards
+#[allow(clippy::collapsible_match)]
 pub fn edit_in_terminal(initial_text: &str) -> Result<String> {
     terminal::enable_raw_mode()?;
 
@@ -177,6 +186,32 @@ pub fn edit_in_terminal(initial_text: &str) -> Result<String> {
     Ok(final_string)
 }
 
+/// Edits a full commit message: the subject inline, then the body and footers in $EDITOR
+pub fn edit_commit_message(message: &str, with_body: bool) -> Result<String> {
+    let (subject, body) = match message.split_once('\n') {
+        Some((subject, rest)) => (subject, rest.trim()),
+        None => (message, ""),
+    };
+
+    let subject = edit_in_terminal(subject)?;
+    if !with_body && body.is_empty() {
+        return Ok(subject);
+    }
+
+    // Unsaved editor sessions keep the previous body
+    let edited_body = Editor::new()
+        .extension(".txt")
+        .edit(body)?
+        .unwrap_or_else(|| body.to_string());
+    let edited_body = edited_body.trim();
+
+    if edited_body.is_empty() {
+        Ok(subject)
+    } else {
+        Ok(format!("{subject}\n\n{edited_body}"))
+    }
+}
+
 /// Shows a selection menu and returns the user's choice
 pub fn show_selection_menu(options: Vec<&str>, prompt: &str) -> Result<usize> {
     let selection = Select::with_theme(&ColorfulTheme::default())
@@ -188,7 +223,63 @@ pub fn show_selection_menu(options: Vec<&str>, prompt: &str) -> Result<usize> {
     Ok(selection)
 }
 
+/// Shows a checklist and returns the indices of the checked items
+pub fn show_multi_select(items: &[String], defaults: &[bool], prompt: &str) -> Result<Vec<usize>> {
+    let selection = MultiSelect::with_theme(&ColorfulTheme::default())
+        .with_prompt(prompt)
+        .items(items)
+        .defaults(defaults)
+        .interact()?;
+
+    Ok(selection)
+}
+
 /// Shows a spinner with the given message
 pub fn show_spinner(message: &str) -> Spinner {
     Spinner::new(Spinners::Dots9, message.into())
 }
+
+/// Set by the Ctrl-C handler while an interruptible operation is running
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+/// Whether Ctrl-C should interrupt the current operation instead of exiting
+static INTERRUPTIBLE: AtomicBool = AtomicBool::new(false);
+static INSTALL_HANDLER: Once = Once::new();
+
+/// Routes Ctrl-C to the current operation until the guard is dropped
+pub struct InterruptGuard;
+
+impl Drop for InterruptGuard {
+    fn drop(&mut self) {
+        INTERRUPTIBLE.store(false, Ordering::SeqCst);
+        INTERRUPTED.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Makes Ctrl-C abort the running operation (see [`interrupted`]) instead of killing
+/// the process. Outside of the guard's lifetime Ctrl-C keeps its usual behaviour.
+pub fn catch_interrupts() -> InterruptGuard {
+    INSTALL_HANDLER.call_once(|| {
+        let _ = ctrlc::set_handler(|| {
+            if INTERRUPTIBLE.load(Ordering::SeqCst) {
+                INTERRUPTED.store(true, Ordering::SeqCst);
+            } else {
+                std::process::exit(130);
+            }
+        });
+    });
+
+    INTERRUPTED.store(false, Ordering::SeqCst);
+    INTERRUPTIBLE.store(true, Ordering::SeqCst);
+    InterruptGuard
+}
+
+/// Returns true once Ctrl-C has been pressed inside [`catch_interrupts`]
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// Prints a chunk of streamed model output as it arrives
+pub fn print_stream_token(token: &str) {
+    print!("{}", style(token).dim());
+    io::stdout().flush().unwrap();
+}